@@ -0,0 +1,217 @@
+use serde::Deserialize;
+use std::error::Error;
+
+use crate::draw::Candle;
+use crate::fechas::Fecha;
+use crate::validacion::{missing_between, trades_weekends};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillMethod {
+    // Repite el último cierre válido
+    ForwardFill,
+    // Interpola linealmente entre los cierres válidos vecinos
+    Interpolate,
+    // Descarta las barras inválidas y no rellena los huecos
+    Drop,
+    // Como ForwardFill, pero la barra queda marcada como hueco
+    MarkGap,
+}
+
+impl FillMethod {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "ffill" | "forward" => Some(FillMethod::ForwardFill),
+            "interpolate" | "linear" => Some(FillMethod::Interpolate),
+            "drop" => Some(FillMethod::Drop),
+            "gap" => Some(FillMethod::MarkGap),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            FillMethod::ForwardFill => "relleno hacia adelante",
+            FillMethod::Interpolate => "interpolación lineal",
+            FillMethod::Drop => "descarte",
+            FillMethod::MarkGap => "marcado de huecos",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FillSummary {
+    pub filled: usize,
+    pub dropped: usize,
+}
+
+pub fn is_bad_bar(candle: &Candle) -> bool {
+    let prices = [candle.open, candle.high, candle.low, candle.close];
+    prices.iter().any(|p| !p.is_finite() || *p <= 0.0)
+        || candle.high < candle.low
+        || candle.close < candle.low
+        || candle.close > candle.high
+}
+
+// Sustituye las barras inválidas y los días sin dato según `method`.
+// Espera velas diarias ordenadas de más antigua a más reciente.
+pub fn fill_bars(bars: &[Candle], method: FillMethod) -> (Vec<Candle>, FillSummary) {
+    let mut summary = FillSummary::default();
+
+    // Cada hueco queda como (fecha, None) hasta que se rellena
+    let dated: Vec<(Fecha, &Candle)> = bars
+        .iter()
+        .filter_map(|c| Fecha::parse(&c.date).map(|f| (f, c)))
+        .collect();
+    summary.dropped += bars.len() - dated.len();
+
+    let weekends = trades_weekends(dated.iter().map(|(fecha, _)| fecha));
+    let mut slots: Vec<(Fecha, Option<Candle>)> = Vec::new();
+    for (i, (fecha, candle)) in dated.iter().enumerate() {
+        if i > 0 && method != FillMethod::Drop {
+            for missing in missing_between(&dated[i - 1].0, fecha, weekends) {
+                slots.push((missing, None));
+            }
+        }
+        if !is_bad_bar(candle) {
+            slots.push((*fecha, Some((*candle).clone())));
+        } else if method == FillMethod::Drop {
+            summary.dropped += 1;
+        } else {
+            slots.push((*fecha, None));
+        }
+    }
+
+    let mut result = Vec::with_capacity(slots.len());
+    for i in 0..slots.len() {
+        let (fecha, slot) = &slots[i];
+        if let Some(candle) = slot {
+            result.push(candle.clone());
+            continue;
+        }
+
+        let prev = slots[..i].iter().rev().find_map(|(f, c)| c.as_ref().map(|c| (f, c.close)));
+        let next = slots[i + 1..].iter().find_map(|(f, c)| c.as_ref().map(|c| (f, c.close)));
+        let value = match (method, prev, next) {
+            (FillMethod::Interpolate, Some((f0, v0)), Some((f1, v1))) => {
+                let t = (fecha.days() - f0.days()) as f64 / (f1.days() - f0.days()) as f64;
+                v0 + (v1 - v0) * t
+            }
+            (_, Some((_, v)), _) | (_, None, Some((_, v))) => v,
+            (_, None, None) => {
+                summary.dropped += 1;
+                continue;
+            }
+        };
+
+        summary.filled += 1;
+        result.push(Candle {
+            date: fecha.to_string(),
+            open: value,
+            high: value,
+            low: value,
+            close: value,
+            volume: 0.0,
+            gap: method == FillMethod::MarkGap,
+        });
+    }
+
+    (result, summary)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CorporateAction {
+    // Acciones nuevas por cada acción antigua (4 para un split 4:1)
+    Split(f64),
+    // Importe del dividendo por acción en la fecha ex-dividendo
+    Dividend(f64),
+}
+
+#[derive(Debug, Clone)]
+pub struct Adjustment {
+    pub fecha: Fecha,
+    pub action: CorporateAction,
+    // Sin símbolo el ajuste se aplica a todos los ficheros cargados
+    pub symbol: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdjustmentRecord {
+    fecha: String,
+    tipo: String,
+    valor: String,
+    #[serde(default)]
+    simbolo: Option<String>,
+}
+
+// Lee un CSV con columnas `fecha,tipo,valor[,simbolo]`, p. ej. `15.06.2022,split,4:1` o `10.03.2023,dividend,0.52,AAPL`
+pub fn load_adjustments(file_path: &str) -> Result<Vec<Adjustment>, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .trim(csv::Trim::All)
+        .from_path(file_path)?;
+    let mut adjustments = Vec::new();
+
+    for result in rdr.deserialize() {
+        let record: AdjustmentRecord = result?;
+        let fecha = Fecha::parse(&record.fecha)
+            .ok_or(format!("Fecha de ajuste inválida: {}", record.fecha))?;
+        let action = match record.tipo.to_lowercase().as_str() {
+            "split" => CorporateAction::Split(parse_ratio(&record.valor)?),
+            "dividend" | "dividendo" => CorporateAction::Dividend(record.valor.parse()?),
+            other => return Err(format!("Tipo de ajuste desconocido: {}", other).into()),
+        };
+        let symbol = record.simbolo.filter(|s| !s.is_empty());
+        adjustments.push(Adjustment { fecha, action, symbol });
+    }
+
+    Ok(adjustments)
+}
+
+fn parse_ratio(value: &str) -> Result<f64, Box<dyn Error>> {
+    let ratio = match value.split_once(':') {
+        Some((new, old)) => new.trim().parse::<f64>()? / old.trim().parse::<f64>()?,
+        None => value.parse()?,
+    };
+    if ratio <= 0.0 {
+        return Err(format!("Proporción de split inválida: {}", value).into());
+    }
+    Ok(ratio)
+}
+
+// Ajusta hacia atrás precios y volúmenes anteriores a cada evento, como hacen los proveedores
+// de datos, para que los indicadores no vean saltos artificiales.
+pub fn apply_adjustments(bars: &mut [Candle], adjustments: &[Adjustment]) {
+    let days: Vec<Option<i64>> = bars.iter().map(|c| Fecha::parse(&c.date).map(|f| f.days())).collect();
+    let mut price_factors = vec![1.0; bars.len()];
+    let mut volume_factors = vec![1.0; bars.len()];
+
+    for adjustment in adjustments {
+        let ex_day = adjustment.fecha.days();
+        let before = |i: usize| days[i].is_some_and(|d| d < ex_day);
+
+        let (price_factor, volume_factor) = match adjustment.action {
+            CorporateAction::Split(ratio) => (1.0 / ratio, ratio),
+            CorporateAction::Dividend(amount) => {
+                // Se usa el último cierre sin ajustar anterior a la fecha ex-dividendo
+                let prev_close = (0..bars.len()).rev().find(|&i| before(i)).map(|i| bars[i].close);
+                match prev_close {
+                    Some(close) if close > amount => (1.0 - amount / close, 1.0),
+                    _ => continue,
+                }
+            }
+        };
+
+        for i in (0..bars.len()).filter(|&i| before(i)) {
+            price_factors[i] *= price_factor;
+            volume_factors[i] *= volume_factor;
+        }
+    }
+
+    for (i, candle) in bars.iter_mut().enumerate() {
+        candle.open *= price_factors[i];
+        candle.high *= price_factors[i];
+        candle.low *= price_factors[i];
+        candle.close *= price_factors[i];
+        candle.volume *= volume_factors[i];
+    }
+}
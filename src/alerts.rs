@@ -0,0 +1,327 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+use crate::fechas::Fecha;
+use crate::overlays::Overlay;
+use crate::workspace::{Symbol, Workspace};
+
+// Condiciones que se comprueban en cada barra. Todas disparan al cruzar, no mientras se
+// mantienen, para no repetir el aviso en cada barra.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    // El cierre cruza un nivel de precio en cualquier sentido
+    PriceCross(f64),
+    // El RSI (14) pasa por encima o por debajo del umbral
+    RsiAbove(f64),
+    RsiBelow(f64),
+    // La SMA rápida cruza la lenta
+    MaCross { fast: usize, slow: usize },
+    // El cierre sale de las bandas de Bollinger (20)
+    BollingerBreak,
+}
+
+impl Condition {
+    // Descripción del disparo en la barra `index`, o None si no se cumple
+    fn check(&self, symbol: &Symbol, index: usize) -> Option<String> {
+        if index == 0 || index >= symbol.bars.len() {
+            return None;
+        }
+        let (prev, close) = (symbol.bars[index - 1].close, symbol.bars[index].close);
+        match *self {
+            Condition::PriceCross(level) => {
+                if prev < level && close >= level {
+                    Some(format!("el cierre cruza al alza ${:.2}", level))
+                } else if prev > level && close <= level {
+                    Some(format!("el cierre cruza a la baja ${:.2}", level))
+                } else {
+                    None
+                }
+            }
+            Condition::RsiAbove(threshold) => {
+                let history = symbol.indicator_history();
+                let (before, rsi) = (history[index - 1].rsi, history[index].rsi);
+                (before <= threshold && rsi > threshold)
+                    .then(|| format!("RSI (14) {:.2} por encima de {}", rsi, threshold))
+            }
+            Condition::RsiBelow(threshold) => {
+                let history = symbol.indicator_history();
+                let (before, rsi) = (history[index - 1].rsi, history[index].rsi);
+                (before >= threshold && rsi < threshold)
+                    .then(|| format!("RSI (14) {:.2} por debajo de {}", rsi, threshold))
+            }
+            Condition::MaCross { fast, slow } => {
+                let fast_line = &symbol.overlay(Overlay::Sma(fast), index - 1..index + 1)[0];
+                let slow_line = &symbol.overlay(Overlay::Sma(slow), index - 1..index + 1)[0];
+                let (before, now) = (fast_line[0] - slow_line[0], fast_line[1] - slow_line[1]);
+                // Mientras alguna media no tiene su periodo completo la diferencia es NaN
+                if before <= 0.0 && now > 0.0 {
+                    Some(format!("cruce alcista SMA {} / SMA {}", fast, slow))
+                } else if before >= 0.0 && now < 0.0 {
+                    Some(format!("cruce bajista SMA {} / SMA {}", fast, slow))
+                } else {
+                    None
+                }
+            }
+            Condition::BollingerBreak => {
+                let bands = symbol.overlay(Overlay::Bollinger(20), index - 1..index + 1);
+                let (upper, lower) = (&bands[0], &bands[2]);
+                if prev <= upper[0] && close > upper[1] {
+                    Some(format!("el cierre rompe la banda superior de Bollinger ${:.2}", upper[1]))
+                } else if prev >= lower[0] && close < lower[1] {
+                    Some(format!("el cierre rompe la banda inferior de Bollinger ${:.2}", lower[1]))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertRule {
+    pub condition: Condition,
+    // Sin símbolo la regla se aplica a todos los ficheros cargados
+    pub symbol: Option<String>,
+}
+
+// Lee un CSV con columnas `tipo,valor[,simbolo]`, p. ej. `precio,50000`, `rsi_sobre,70,BTC`,
+// `rsi_bajo,30`, `cruce_medias,20:50` o `bollinger,`
+pub fn load_alert_rules(file_path: &str) -> Result<Vec<AlertRule>, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(file_path)?;
+    let mut rules = Vec::new();
+
+    // Por posición y no por nombre: las reglas sin valor ni símbolo pueden acortar la fila
+    for result in rdr.records() {
+        let record = result?;
+        let tipo = record.get(0).unwrap_or_default();
+        let valor = record.get(1).unwrap_or_default();
+        let condition = match tipo.to_lowercase().as_str() {
+            "precio" => Condition::PriceCross(valor.parse()?),
+            "rsi_sobre" => Condition::RsiAbove(valor.parse()?),
+            "rsi_bajo" => Condition::RsiBelow(valor.parse()?),
+            "cruce_medias" => {
+                let (fast, slow) = valor
+                    .split_once(':')
+                    .ok_or(format!("Cruce de medias inválido, se espera rápida:lenta: {}", valor))?;
+                Condition::MaCross { fast: fast.trim().parse()?, slow: slow.trim().parse()? }
+            }
+            "bollinger" => Condition::BollingerBreak,
+            other => return Err(format!("Tipo de alerta desconocido: {}", other).into()),
+        };
+        let symbol = record.get(2).filter(|s| !s.is_empty()).map(str::to_string);
+        rules.push(AlertRule { condition, symbol });
+    }
+
+    Ok(rules)
+}
+
+// Alerta disparada en una barra
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub symbol: String,
+    pub date: String,
+    pub message: String,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}: {}", self.symbol, self.date, self.message)
+    }
+}
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_millis(500);
+
+// Receptor HTTP local de las alertas; sólo http://, sin TLS
+#[derive(Debug, Clone, PartialEq)]
+pub struct Webhook {
+    address: String,
+    host: String,
+    path: String,
+}
+
+impl Webhook {
+    // "http://127.0.0.1:8080/alertas"; sin puerto se usa el 80
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url.strip_prefix("http://")?;
+        let (host, path) = match rest.find('/') {
+            Some(slash) => (&rest[..slash], &rest[slash..]),
+            None => (rest, "/"),
+        };
+        if host.is_empty() {
+            return None;
+        }
+        let address = if host.contains(':') { host.to_string() } else { format!("{}:80", host) };
+        Some(Webhook { address, host: host.to_string(), path: path.to_string() })
+    }
+
+    // Envía la alerta como JSON en un POST y comprueba que la respuesta sea 2xx
+    fn post(&self, alert: &Alert) -> Result<(), Box<dyn Error>> {
+        let body = serde_json::json!({
+            "simbolo": alert.symbol,
+            "fecha": alert.date,
+            "mensaje": alert.message,
+        })
+        .to_string();
+        let address = self.address.to_socket_addrs()?.next().ok_or("dirección del webhook sin resolver")?;
+        let mut stream = TcpStream::connect_timeout(&address, WEBHOOK_TIMEOUT)?;
+        stream.set_write_timeout(Some(WEBHOOK_TIMEOUT))?;
+        stream.set_read_timeout(Some(WEBHOOK_TIMEOUT))?;
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.path,
+            self.host,
+            body.len(),
+            body,
+        );
+        stream.write_all(request.as_bytes())?;
+
+        // Basta con la línea de estado, p. ej. "HTTP/1.1 204 No Content"
+        let mut response = [0u8; 64];
+        let read = stream.read(&mut response)?;
+        let status = String::from_utf8_lossy(&response[..read]);
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') => Ok(()),
+            Some(code) => Err(format!("el webhook respondió {}", code).into()),
+            None => Err("el webhook no respondió".into()),
+        }
+    }
+
+    // Hilo que envía las alertas que le llegan por el canal, para que un webhook lento no
+    // congele la interfaz. Los fallos vuelven por el otro canal.
+    fn spawn(self) -> WebhookWorker {
+        let (alerts, pending) = mpsc::channel::<Alert>();
+        let (failed, errors) = mpsc::channel();
+        thread::spawn(move || {
+            for alert in pending {
+                if let Err(e) = self.post(&alert) {
+                    // Si la interfaz ya se cerró no queda nadie a quien avisar
+                    if failed.send(e.to_string()).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        WebhookWorker { alerts, errors }
+    }
+}
+
+// Canales del hilo del webhook: alertas por enviar y fallos de envío
+#[derive(Debug)]
+struct WebhookWorker {
+    alerts: Sender<Alert>,
+    errors: Receiver<String>,
+}
+
+// Evalúa las reglas sobre las barras que van llegando y entrega cada alerta al registro y al webhook
+#[derive(Debug)]
+pub struct AlertMonitor {
+    rules: Vec<AlertRule>,
+    log_path: String,
+    webhook: Option<WebhookWorker>,
+    // Fecha y cierre de la última barra evaluada de cada símbolo
+    last_seen: HashMap<String, (String, f64)>,
+    // Regla, símbolo y fecha de cada alerta ya disparada, para no repetirla si la sesión en
+    // curso se reescribe
+    fired: HashSet<(usize, String, String)>,
+}
+
+impl AlertMonitor {
+    pub fn new(rules: Vec<AlertRule>, log_path: String, webhook: Option<Webhook>) -> Self {
+        AlertMonitor {
+            rules,
+            log_path,
+            webhook: webhook.map(Webhook::spawn),
+            last_seen: HashMap::new(),
+            fired: HashSet::new(),
+        }
+    }
+
+    // Evalúa las barras posteriores a la última vista de cada símbolo, y la última vista otra
+    // vez si cambió su cierre. La primera vez sólo se evalúa la última barra. Devuelve los
+    // avisos para la barra de notificaciones, incluidos los fallos de entrega.
+    pub fn check(&mut self, workspace: &Workspace) -> Vec<String> {
+        // Los envíos al webhook terminan después; sus fallos se avisan en cuanto se conocen
+        let mut notices: Vec<String> = match &self.webhook {
+            Some(worker) => worker.errors.try_iter().map(|e| format!("alertas: webhook: {}", e)).collect(),
+            None => Vec::new(),
+        };
+        for symbol in &workspace.symbols {
+            let Some(last) = symbol.bars.last() else { continue };
+            let start = match self.last_seen.get(&symbol.name) {
+                None => symbol.bars.len() - 1,
+                Some((date, close)) => match Fecha::parse(date) {
+                    Some(seen) => {
+                        let after = symbol.bars.partition_point(|c| Fecha::parse(&c.date).is_none_or(|f| f <= seen));
+                        match after.checked_sub(1).map(|i| &symbol.bars[i]) {
+                            Some(bar) if bar.date == *date && bar.close != *close => after - 1,
+                            _ => after,
+                        }
+                    }
+                    None => symbol.bars.len(),
+                },
+            };
+            self.last_seen.insert(symbol.name.clone(), (last.date.clone(), last.close));
+
+            for index in start..symbol.bars.len() {
+                for (rule_index, rule) in self.rules.iter().enumerate() {
+                    if rule.symbol.as_ref().is_some_and(|s| *s != symbol.name) {
+                        continue;
+                    }
+                    let date = symbol.bars[index].date.clone();
+                    if self.fired.contains(&(rule_index, symbol.name.clone(), date.clone())) {
+                        continue;
+                    }
+                    if let Some(message) = rule.condition.check(symbol, index) {
+                        self.fired.insert((rule_index, symbol.name.clone(), date.clone()));
+                        let alert = Alert { symbol: symbol.name.clone(), date, message };
+                        notices.push(alert.to_string());
+                        notices.extend(self.deliver(alert).into_iter().map(|e| format!("alertas: {}", e)));
+                    }
+                }
+            }
+        }
+        notices
+    }
+
+    // Da por vistas las barras actuales sin evaluarlas. Tras cambiar de temporalidad o salir de
+    // un replay la serie cambia entera y sus barras no son nuevas.
+    pub fn mark_seen(&mut self, workspace: &Workspace) {
+        for symbol in &workspace.symbols {
+            if let Some(last) = symbol.bars.last() {
+                self.last_seen.insert(symbol.name.clone(), (last.date.clone(), last.close));
+            }
+        }
+    }
+
+    // Entrega la alerta al registro y al webhook por separado, de modo que el fallo de uno no
+    // impide el otro. Devuelve los fallos del registro y del paso de la alerta al hilo del webhook.
+    fn deliver(&self, alert: Alert) -> Vec<String> {
+        let mut errors = Vec::new();
+        if let Err(e) = append_to_log(&self.log_path, &alert) {
+            errors.push(format!("registro: {}", e));
+        }
+        if let Some(worker) = &self.webhook {
+            if worker.alerts.send(alert).is_err() {
+                errors.push("webhook: el hilo de envío terminó".to_string());
+            }
+        }
+        errors
+    }
+}
+
+fn append_to_log(path: &str, alert: &Alert) -> Result<(), Box<dyn Error>> {
+    let mut log = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(log, "{}", alert)?;
+    Ok(())
+}
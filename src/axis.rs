@@ -0,0 +1,135 @@
+// Dominio de valores de un eje vertical. Cada indicador declara el suyo y el gráfico construye
+// los límites y las etiquetas a partir de él. Los límites están en coordenadas del eje, que
+// sólo difieren del valor en la escala logarítmica: ver `coordinate`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AxisDomain {
+    // Como `Auto` pero con etiquetas en dólares
+    Price,
+    // Precio en escala logarítmica (log10), para series que recorren varios órdenes de magnitud
+    LogPrice,
+    // Límites fijos, p. ej. 0 a 100 para el RSI
+    Fixed(f64, f64),
+    // Simétrico alrededor de cero, para osciladores como el MACD
+    ZeroCentered,
+    // Mínimo y máximo de los valores con un pequeño margen
+    Auto,
+}
+
+// Etiquetas que se reparten a lo largo de un eje
+pub const LABELS: usize = 5;
+
+// Mantisas de las marcas logarítmicas, de la más densa a la más espaciada
+const LOG_MANTISSAS: [&[f64]; 3] = [&[1.0, 2.0, 3.0, 5.0], &[1.0, 2.0, 5.0], &[1.0]];
+
+// Filas libres mínimas entre dos marcas logarítmicas
+const LOG_TICK_GAP: usize = 2;
+
+impl AxisDomain {
+    // Coordenada en el eje de un valor: su log10 en la escala logarítmica, donde los valores
+    // no positivos quedan fuera (NaN), y el propio valor en las demás
+    pub fn coordinate(&self, value: f64) -> f64 {
+        match self {
+            AxisDomain::LogPrice if value > 0.0 => value.log10(),
+            AxisDomain::LogPrice => f64::NAN,
+            _ => value,
+        }
+    }
+
+    // Inversa de `coordinate`
+    pub fn value_at(&self, coordinate: f64) -> f64 {
+        match self {
+            AxisDomain::LogPrice => 10f64.powf(coordinate),
+            _ => coordinate,
+        }
+    }
+
+    // Límites en coordenadas del eje para los valores dados
+    pub fn bounds(&self, values: impl IntoIterator<Item = f64>) -> [f64; 2] {
+        if let AxisDomain::Fixed(min, max) = *self {
+            return [min, max];
+        }
+        let (min, max) = values
+            .into_iter()
+            .map(|v| self.coordinate(v))
+            .filter(|v| v.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(v), max.max(v)));
+        if min > max {
+            return [-1.0, 1.0];
+        }
+
+        match self {
+            AxisDomain::ZeroCentered => {
+                let extent = min.abs().max(max.abs()).max(f64::EPSILON) * 1.05;
+                [-extent, extent]
+            }
+            // En log10 el margen es proporcional y no hay cero que respetar
+            AxisDomain::LogPrice => {
+                let range = (max - min).max(0.01);
+                [min - range * 0.05, max + range * 0.05]
+            }
+            _ => {
+                // Una ventana con todos los valores iguales necesita algo de margen para verse
+                let range = if max > min { max - min } else { max.abs().max(1.0) * 0.1 };
+                // Con valores no negativos (precio, volumen) el eje no baja de cero
+                let lower = min - range * 0.05;
+                let lower = if min >= 0.0 { lower.max(0.0) } else { lower };
+                [lower, max + range * 0.05]
+            }
+        }
+    }
+
+    // Etiquetas de abajo arriba para un eje de `rows` filas. Las escalas lineales dan `LABELS`
+    // etiquetas equiespaciadas; la logarítmica da una por fila, vacía salvo donde cae un valor
+    // redondo, porque `Chart` reparte las etiquetas por igual y así cada una queda a su altura.
+    pub fn labels(&self, bounds: [f64; 2], rows: u16) -> Vec<String> {
+        if *self == AxisDomain::LogPrice {
+            if let Some(labels) = log_labels(bounds, rows as usize) {
+                return labels;
+            }
+        }
+        // Sin marcas redondas suficientes la escala logarítmica reparte también `LABELS`
+        let step = (bounds[1] - bounds[0]) / (LABELS - 1) as f64;
+        (0..LABELS).map(|i| self.format(self.value_at(bounds[0] + step * i as f64))).collect()
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        if matches!(self, AxisDomain::Price | AxisDomain::LogPrice) {
+            format!("${:.0}", value)
+        } else if value.abs() >= 100.0 || value.fract() == 0.0 {
+            format!("{:.0}", value)
+        } else {
+            format!("{:.2}", value)
+        }
+    }
+}
+
+// Marcas en valores redondos (mantisa × 10ⁿ) dentro de los límites, con la serie de mantisas
+// más densa que deja al menos `LOG_TICK_GAP` filas entre marcas. `None` si no caben dos.
+fn log_labels([low, high]: [f64; 2], rows: usize) -> Option<Vec<String>> {
+    if rows < 2 || high <= low {
+        return None;
+    }
+    let row_of = |coordinate: f64| ((coordinate - low) / (high - low) * (rows - 1) as f64).round() as usize;
+    for mantissas in LOG_MANTISSAS {
+        let mut ticks: Vec<(usize, f64)> = Vec::new();
+        for exponent in low.floor() as i32..=high.ceil() as i32 {
+            for mantissa in mantissas {
+                let coordinate = mantissa.log10() + exponent as f64;
+                if (low..=high).contains(&coordinate) {
+                    ticks.push((row_of(coordinate), mantissa * 10f64.powi(exponent)));
+                }
+            }
+        }
+        let spaced = ticks.windows(2).all(|pair| pair[1].0 > pair[0].0 + LOG_TICK_GAP);
+        if ticks.len() >= 2 && spaced {
+            let mut labels = vec![String::new(); rows];
+            for (row, value) in ticks {
+                // Por debajo de 1 hacen falta decimales para distinguir las marcas
+                let decimals = (-value.log10().floor()).max(0.0) as usize;
+                labels[row] = format!("${:.*}", decimals, value);
+            }
+            return Some(labels);
+        }
+    }
+    None
+}
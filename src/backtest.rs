@@ -0,0 +1,427 @@
+use std::mem;
+
+use crate::draw::Candle;
+use crate::indicadores::IndicatorValues;
+use crate::signals::{Side, SignalRule};
+use crate::workspace::Symbol;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderKind {
+    // A la apertura de la barra siguiente
+    Market,
+    // Compra a este precio o por debajo; venta a este precio o por encima
+    Limit(f64),
+    // Se activa cuando el precio toca el nivel: compra al subir, venta al bajar
+    Stop(f64),
+}
+
+// Orden que la estrategia envía al cierre de una barra. Sólo vale para la siguiente barra real:
+// si no se ejecuta en ella se cancela y la estrategia tiene que volver a enviarla.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Order {
+    pub side: Side,
+    pub quantity: f64,
+    pub kind: OrderKind,
+    // Sólo cierra posición: se recorta a la posición abierta y no abre la contraria. Con dos
+    // salidas en la misma barra (stop y objetivo) se ejecuta la primera y la otra queda sin efecto.
+    pub reduce_only: bool,
+}
+
+impl Order {
+    pub fn market(side: Side, quantity: f64) -> Self {
+        Order { side, quantity, kind: OrderKind::Market, reduce_only: false }
+    }
+
+    // Precio de ejecución en la barra, o None si no llega al nivel. Si la barra abre ya más
+    // allá del nivel se ejecuta a la apertura.
+    fn fill_price(&self, bar: &Candle) -> Option<f64> {
+        match (self.kind, self.side) {
+            (OrderKind::Market, _) => Some(bar.open),
+            (OrderKind::Limit(price), Side::Buy) => (bar.low <= price).then_some(bar.open.min(price)),
+            (OrderKind::Limit(price), Side::Sell) => (bar.high >= price).then_some(bar.open.max(price)),
+            (OrderKind::Stop(price), Side::Buy) => (bar.high >= price).then_some(bar.open.max(price)),
+            (OrderKind::Stop(price), Side::Sell) => (bar.low <= price).then_some(bar.open.min(price)),
+        }
+    }
+}
+
+// Posición abierta; cantidad positiva en largo y negativa en corto
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Position {
+    pub quantity: f64,
+    // Precio medio de entrada
+    pub entry_price: f64,
+    // Barra de la primera entrada
+    pub entry_index: usize,
+}
+
+// Estado de la cuenta que ve la estrategia al cierre de cada barra
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Account {
+    pub cash: f64,
+    pub position: Position,
+}
+
+impl Account {
+    // Efectivo más la posición valorada a `price`
+    pub fn equity(&self, price: f64) -> f64 {
+        self.cash + self.position.quantity * price
+    }
+}
+
+// Operación cerrada, de la entrada a la salida
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    // Compra para los largos, venta para los cortos
+    pub side: Side,
+    pub quantity: f64,
+    pub entry_index: usize,
+    pub entry_price: f64,
+    pub exit_index: usize,
+    pub exit_price: f64,
+}
+
+impl Trade {
+    pub fn profit(&self) -> f64 {
+        let direction = if self.side == Side::Buy { 1.0 } else { -1.0 };
+        (self.exit_price - self.entry_price) * self.quantity * direction
+    }
+
+    // Resultado en % sobre el precio de entrada
+    pub fn return_pct(&self) -> f64 {
+        self.profit() / (self.entry_price * self.quantity) * 100.0
+    }
+}
+
+// Estrategia que decide al cierre de cada barra. Recibe las barras e indicadores hasta la
+// barra actual incluida, nunca los siguientes, y las órdenes que devuelve se ejecutan en la
+// barra siguiente.
+pub trait Strategy {
+    fn name(&self) -> String;
+    fn on_bar(&mut self, bars: &[Candle], values: &[IndicatorValues], account: &Account) -> Vec<Order>;
+}
+
+// Bróker simulado: ejecuta las órdenes pendientes en la barra siguiente, sin comisiones ni
+// deslizamiento, y sin apalancamiento en las compras
+#[derive(Debug)]
+struct Broker {
+    account: Account,
+    pending: Vec<Order>,
+    trades: Vec<Trade>,
+}
+
+impl Broker {
+    // Ejecuta en la barra `index` las órdenes enviadas al cierre de la anterior, en el orden
+    // en que se enviaron. Las barras que rellenan un hueco no tienen precios reales: en ellas
+    // no se ejecuta nada y las órdenes esperan a la siguiente.
+    fn execute(&mut self, index: usize, bar: &Candle) {
+        if bar.gap {
+            return;
+        }
+        for order in mem::take(&mut self.pending) {
+            if let Some(price) = order.fill_price(bar) {
+                self.fill(index, &order, price);
+            }
+        }
+    }
+
+    fn fill(&mut self, index: usize, order: &Order, price: f64) {
+        let position = self.account.position;
+        let direction = if order.side == Side::Buy { 1.0 } else { -1.0 };
+        // Parte de la orden que cierra la posición abierta en sentido contrario
+        let closing = if position.quantity * direction < 0.0 { position.quantity.abs() } else { 0.0 };
+        let mut quantity = order.quantity.max(0.0);
+        if order.reduce_only {
+            quantity = quantity.min(closing);
+        } else if order.side == Side::Buy {
+            // Lo que pase de cerrar el corto se paga con el efectivo disponible
+            let opening = (quantity - closing).max(0.0);
+            let affordable = ((self.account.cash - closing * price) / price).max(0.0);
+            quantity = closing.min(quantity) + opening.min(affordable);
+        }
+        if quantity <= 0.0 {
+            return;
+        }
+
+        let closed = quantity.min(closing);
+        if closed > 0.0 {
+            self.trades.push(Trade {
+                side: if position.quantity > 0.0 { Side::Buy } else { Side::Sell },
+                quantity: closed,
+                entry_index: position.entry_index,
+                entry_price: position.entry_price,
+                exit_index: index,
+                exit_price: price,
+            });
+        }
+
+        let new_quantity = position.quantity + quantity * direction;
+        self.account.cash -= quantity * direction * price;
+        self.account.position = if new_quantity.abs() < 1e-12 {
+            Position::default()
+        } else if closed < closing {
+            // Cierre parcial: la entrada no cambia
+            Position { quantity: new_quantity, ..position }
+        } else if closing > 0.0 {
+            // La orden da la vuelta a la posición: lo que sobra abre la contraria
+            Position { quantity: new_quantity, entry_price: price, entry_index: index }
+        } else if position.quantity == 0.0 {
+            Position { quantity: new_quantity, entry_price: price, entry_index: index }
+        } else {
+            // Se amplía la posición: nuevo precio medio
+            let entry_price =
+                (position.entry_price * position.quantity.abs() + price * quantity) / new_quantity.abs();
+            Position { quantity: new_quantity, entry_price, entry_index: position.entry_index }
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestResult {
+    pub strategy: String,
+    pub capital: f64,
+    pub trades: Vec<Trade>,
+    // Capital de la cuenta al cierre de cada barra
+    pub equity: Vec<f64>,
+    // Posición que sigue abierta en la última barra; se valora en la curva pero no es una operación
+    pub open_position: Position,
+}
+
+impl BacktestResult {
+    pub fn final_equity(&self) -> f64 {
+        self.equity.last().copied().unwrap_or(self.capital)
+    }
+
+    pub fn total_return_pct(&self) -> f64 {
+        (self.final_equity() / self.capital - 1.0) * 100.0
+    }
+
+    // % de operaciones cerradas con beneficio
+    pub fn win_rate(&self) -> Option<f64> {
+        if self.trades.is_empty() {
+            return None;
+        }
+        let wins = self.trades.iter().filter(|t| t.profit() > 0.0).count();
+        Some(wins as f64 / self.trades.len() as f64 * 100.0)
+    }
+
+    // Mayor caída en % desde un máximo anterior de la curva de capital
+    pub fn max_drawdown_pct(&self) -> f64 {
+        let mut peak = f64::NEG_INFINITY;
+        let mut drawdown: f64 = 0.0;
+        for &equity in &self.equity {
+            peak = peak.max(equity);
+            drawdown = drawdown.max((peak - equity) / peak * 100.0);
+        }
+        drawdown
+    }
+}
+
+// Recorre la serie barra a barra: primero se ejecutan las órdenes pendientes a la apertura,
+// después se anota el capital al cierre y por último la estrategia envía las órdenes de la
+// barra siguiente. En las barras de relleno la estrategia no decide y las órdenes pendientes
+// pasan a la siguiente.
+pub fn run(strategy: &mut dyn Strategy, bars: &[Candle], values: &[IndicatorValues], capital: f64) -> BacktestResult {
+    let mut broker = Broker {
+        account: Account { cash: capital, position: Position::default() },
+        pending: Vec::new(),
+        trades: Vec::new(),
+    };
+    let len = bars.len().min(values.len());
+    let mut equity = Vec::with_capacity(len);
+
+    for index in 0..len {
+        broker.execute(index, &bars[index]);
+        equity.push(broker.account.equity(bars[index].close));
+        if !bars[index].gap {
+            broker.pending = strategy.on_bar(&bars[..=index], &values[..=index], &broker.account);
+        }
+    }
+
+    BacktestResult {
+        strategy: strategy.name(),
+        capital,
+        trades: broker.trades,
+        equity,
+        open_position: broker.account.position,
+    }
+}
+
+// Estrategia de las señales de signals.rs, sólo en largo: compra con todo el efectivo en una
+// señal de compra y vende en una de venta. El stop de pérdidas y el objetivo de beneficio, en %
+// sobre la entrada, se envían en cada barra mientras la posición está abierta.
+#[derive(Debug, Clone)]
+pub struct SignalStrategy {
+    pub rules: Vec<SignalRule>,
+    pub stop_loss: Option<f64>,
+    pub take_profit: Option<f64>,
+}
+
+impl Strategy for SignalStrategy {
+    fn name(&self) -> String {
+        let mut name = self.rules.iter().map(|r| r.as_str()).collect::<Vec<_>>().join(", ");
+        if let Some(stop) = self.stop_loss {
+            name.push_str(&format!(" · stop -{}%", stop));
+        }
+        if let Some(target) = self.take_profit {
+            name.push_str(&format!(" · objetivo +{}%", target));
+        }
+        name
+    }
+
+    fn on_bar(&mut self, bars: &[Candle], values: &[IndicatorValues], account: &Account) -> Vec<Order> {
+        let index = bars.len() - 1;
+        let sides: Vec<Side> = self.rules.iter().filter_map(|rule| rule.check(bars, values, index)).collect();
+        let position = account.position;
+
+        if position.quantity <= 0.0 {
+            // Con señales contrarias en la misma barra no se entra
+            if sides.contains(&Side::Buy) && !sides.contains(&Side::Sell) {
+                return vec![Order::market(Side::Buy, account.cash / bars[index].close)];
+            }
+            return Vec::new();
+        }
+
+        let exit = |kind| Order { side: Side::Sell, quantity: position.quantity, kind, reduce_only: true };
+        if sides.contains(&Side::Sell) {
+            return vec![exit(OrderKind::Market)];
+        }
+        let mut orders = Vec::new();
+        // El stop va primero: si la barra toca los dos niveles se supone lo peor
+        if let Some(stop) = self.stop_loss {
+            orders.push(exit(OrderKind::Stop(position.entry_price * (1.0 - stop / 100.0))));
+        }
+        if let Some(target) = self.take_profit {
+            orders.push(exit(OrderKind::Limit(position.entry_price * (1.0 + target / 100.0))));
+        }
+        orders
+    }
+}
+
+// Capital inicial y salidas de la estrategia de señales
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BacktestSettings {
+    pub capital: f64,
+    pub stop_loss: Option<f64>,
+    pub take_profit: Option<f64>,
+}
+
+impl Default for BacktestSettings {
+    fn default() -> Self {
+        BacktestSettings { capital: 10_000.0, stop_loss: None, take_profit: None }
+    }
+}
+
+// Estrategia de señales con todas las reglas sobre la serie completa del símbolo
+pub fn backtest_signals(symbol: &Symbol, settings: &BacktestSettings) -> BacktestResult {
+    let mut strategy = SignalStrategy {
+        rules: SignalRule::ALL.to_vec(),
+        stop_loss: settings.stop_loss,
+        take_profit: settings.take_profit,
+    };
+    run(&mut strategy, &symbol.bars, symbol.indicator_history(), settings.capital)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(open: f64, high: f64, low: f64, close: f64) -> Candle {
+        Candle { date: "01.01.2024".to_string(), open, high, low, close, volume: 1.0, gap: false }
+    }
+
+    fn broker(cash: f64, position: Position) -> Broker {
+        Broker { account: Account { cash, position }, pending: Vec::new(), trades: Vec::new() }
+    }
+
+    // Largo de 10 a 100 abierto en la barra 0, sin efectivo
+    fn long() -> Broker {
+        broker(0.0, Position { quantity: 10.0, entry_price: 100.0, entry_index: 0 })
+    }
+
+    fn order(side: Side, quantity: f64, kind: OrderKind) -> Order {
+        Order { side, quantity, kind, reduce_only: false }
+    }
+
+    #[test]
+    fn limit_and_stop_fill_at_level_or_at_open_past_it() {
+        let inside = bar(100.0, 106.0, 94.0, 101.0);
+        assert_eq!(order(Side::Buy, 1.0, OrderKind::Limit(95.0)).fill_price(&inside), Some(95.0));
+        assert_eq!(order(Side::Sell, 1.0, OrderKind::Stop(95.0)).fill_price(&inside), Some(95.0));
+        assert_eq!(order(Side::Buy, 1.0, OrderKind::Limit(90.0)).fill_price(&inside), None);
+        assert_eq!(order(Side::Buy, 1.0, OrderKind::Stop(110.0)).fill_price(&inside), None);
+
+        // La barra abre ya más allá del nivel: se ejecuta a la apertura
+        let gap_down = bar(90.0, 92.0, 88.0, 91.0);
+        assert_eq!(order(Side::Buy, 1.0, OrderKind::Limit(95.0)).fill_price(&gap_down), Some(90.0));
+        assert_eq!(order(Side::Sell, 1.0, OrderKind::Stop(95.0)).fill_price(&gap_down), Some(90.0));
+        let gap_up = bar(110.0, 112.0, 108.0, 111.0);
+        assert_eq!(order(Side::Sell, 1.0, OrderKind::Limit(105.0)).fill_price(&gap_up), Some(110.0));
+        assert_eq!(order(Side::Buy, 1.0, OrderKind::Stop(105.0)).fill_price(&gap_up), Some(110.0));
+    }
+
+    #[test]
+    fn reduce_only_exits_once_when_stop_and_target_hit_in_same_bar() {
+        let mut broker = long();
+        let exit = |kind| Order { side: Side::Sell, quantity: 10.0, kind, reduce_only: true };
+        broker.pending = vec![exit(OrderKind::Stop(95.0)), exit(OrderKind::Limit(110.0))];
+        broker.execute(1, &bar(100.0, 112.0, 94.0, 105.0));
+
+        assert_eq!(broker.trades.len(), 1);
+        assert_eq!(broker.trades[0].exit_price, 95.0);
+        assert_eq!(broker.account.position, Position::default());
+        assert_eq!(broker.account.cash, 950.0);
+    }
+
+    #[test]
+    fn partial_close_keeps_entry() {
+        let mut broker = long();
+        broker.fill(3, &Order::market(Side::Sell, 4.0), 120.0);
+
+        assert_eq!(broker.trades.len(), 1);
+        assert_eq!((broker.trades[0].quantity, broker.trades[0].exit_index), (4.0, 3));
+        assert_eq!(broker.trades[0].profit(), 80.0);
+        assert_eq!(broker.account.position, Position { quantity: 6.0, entry_price: 100.0, entry_index: 0 });
+        assert_eq!(broker.account.cash, 480.0);
+    }
+
+    #[test]
+    fn reversal_opens_opposite_position_at_fill() {
+        let mut broker = long();
+        broker.fill(5, &Order::market(Side::Sell, 15.0), 120.0);
+
+        assert_eq!(broker.trades.len(), 1);
+        assert_eq!(broker.trades[0].side, Side::Buy);
+        assert_eq!(broker.trades[0].quantity, 10.0);
+        assert_eq!(broker.account.position, Position { quantity: -5.0, entry_price: 120.0, entry_index: 5 });
+        assert_eq!(broker.account.cash, 1800.0);
+    }
+
+    #[test]
+    fn buys_are_capped_by_cash() {
+        let mut flat = broker(1000.0, Position::default());
+        flat.fill(1, &Order::market(Side::Buy, 20.0), 100.0);
+        assert_eq!(flat.account.position.quantity, 10.0);
+        assert_eq!(flat.account.cash, 0.0);
+
+        // Cerrar un corto se paga primero; sólo lo que sobra abre el largo
+        let mut short = broker(1500.0, Position { quantity: -5.0, entry_price: 100.0, entry_index: 0 });
+        short.fill(1, &Order::market(Side::Buy, 20.0), 100.0);
+        assert_eq!(short.trades[0].quantity, 5.0);
+        assert_eq!(short.account.position, Position { quantity: 10.0, entry_price: 100.0, entry_index: 1 });
+        assert_eq!(short.account.cash, 0.0);
+    }
+
+    #[test]
+    fn gap_bars_leave_orders_pending() {
+        let mut broker = broker(1000.0, Position::default());
+        broker.pending = vec![Order::market(Side::Buy, 5.0)];
+        broker.execute(1, &Candle { gap: true, ..bar(90.0, 90.0, 90.0, 90.0) });
+        assert_eq!(broker.account.position, Position::default());
+        assert_eq!(broker.pending.len(), 1);
+
+        broker.execute(2, &bar(100.0, 101.0, 99.0, 100.0));
+        assert_eq!(broker.account.position, Position { quantity: 5.0, entry_price: 100.0, entry_index: 2 });
+        assert!(broker.pending.is_empty());
+    }
+}
@@ -0,0 +1,104 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::overlays::Overlay;
+
+// Indicador y parámetros con los que se calculó un valor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndicatorKey {
+    // Señales de compra y venta sobre la serie completa
+    Signals,
+    // Líneas de una superposición sobre la serie completa
+    Overlay(Overlay),
+    // Perfil de volumen de las barras visibles; guarda sólo el de la última ventana pedida
+    VisibleProfile,
+    // Backtest de la estrategia de señales; guarda sólo el de los últimos ajustes
+    Backtest,
+    // Entre símbolos, en la caché del espacio de trabajo: matriz de correlación y correlación
+    // móvil del símbolo seleccionado, con el último método y la última referencia
+    CorrelationMatrix,
+    RollingCorrelation,
+    // Series normalizadas de la vista de comparación, con la última normalización y fecha base
+    Comparison,
+}
+
+// Versión de los datos, valor calculado y, si el final de la serie cambió después de
+// calcularlo, cuántas barras del principio siguen valiendo
+type Entry = (u64, Rc<dyn Any>, Option<usize>);
+
+// Resultados de indicadores guardados entre fotogramas. Sólo se guarda el valor de la versión
+// actual de los datos de cada indicador: al cambiar la serie entera se vacía y, si sólo cambia
+// su final, lo calculado sobre las barras anteriores se conserva para extenderlo.
+#[derive(Clone, Default)]
+pub struct IndicatorCache {
+    entries: RefCell<HashMap<IndicatorKey, Entry>>,
+}
+
+impl IndicatorCache {
+    // Valor de `key` para la versión `version` de los datos. Si el guardado sólo vale para las
+    // primeras barras, `update` lo recibe junto con cuántas son para extenderlo en lugar de
+    // recalcularlo; si no hay ninguno lo calcula entero.
+    pub fn get_or_update<T: Clone + 'static>(
+        &self,
+        key: IndicatorKey,
+        version: u64,
+        update: impl FnOnce(Option<(&T, usize)>) -> T,
+    ) -> T {
+        let value = {
+            let entries = self.entries.borrow();
+            let entry = entries.get(&key).filter(|(entry_version, _, _)| *entry_version == version);
+            match entry.and_then(|(_, value, valid)| Some((value.downcast_ref::<T>()?, *valid))) {
+                Some((value, None)) => return value.clone(),
+                Some((value, Some(valid))) => update(Some((value, valid))),
+                None => update(None),
+            }
+        };
+        self.entries.borrow_mut().insert(key, (version, Rc::new(value.clone()), None));
+        value
+    }
+
+    // Para las claves de un solo hueco, como el perfil visible: el valor se guarda con los
+    // parámetros con que se calculó y se sustituye en cuanto éstos cambian
+    pub fn get_or_replace<P: PartialEq + 'static, T: Clone + 'static>(
+        &self,
+        key: IndicatorKey,
+        version: u64,
+        params: P,
+        compute: impl FnOnce(&P) -> T,
+    ) -> T {
+        if let Some((entry_version, value, None)) = self.entries.borrow().get(&key) {
+            if let (true, Some((entry_params, value))) = (*entry_version == version, value.downcast_ref::<(P, T)>()) {
+                if *entry_params == params {
+                    return value.clone();
+                }
+            }
+        }
+
+        let value = compute(&params);
+        self.entries.borrow_mut().insert(key, (version, Rc::new((params, value.clone())), None));
+        value
+    }
+
+    // La serie pasa a `version` y cambió desde la barra `from`; lo anterior sigue valiendo
+    pub fn truncate(&self, version: u64, from: usize) {
+        let mut entries = self.entries.borrow_mut();
+        entries.retain(|_, (entry_version, _, valid)| {
+            *entry_version = version;
+            *valid = Some(valid.map_or(from, |valid| valid.min(from)));
+            *valid != Some(0)
+        });
+    }
+
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+}
+
+impl fmt::Debug for IndicatorCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.entries.borrow().keys()).finish()
+    }
+}
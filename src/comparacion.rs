@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+
+use crate::fechas::Fecha;
+use crate::workspace::Workspace;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Normalization {
+    // Todas las series valen 100 en la fecha base
+    #[default]
+    Base100,
+    // Variación porcentual respecto a la fecha base
+    Percent,
+}
+
+impl Normalization {
+    pub fn next(&self) -> Self {
+        match self {
+            Normalization::Base100 => Normalization::Percent,
+            Normalization::Percent => Normalization::Base100,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Normalization::Base100 => "Base 100",
+            Normalization::Percent => "% cambio",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComparisonSettings {
+    pub normalization: Normalization,
+    // Sin fecha elegida se usa la primera en la que todas las series tienen dato
+    pub base_date: Option<Fecha>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    // Calendario común: unión de las fechas de todas las series
+    pub dates: Vec<Fecha>,
+    pub base: Fecha,
+    // (nombre, puntos (índice en `dates`, valor normalizado))
+    pub series: Vec<(String, Vec<(f64, f64)>)>,
+}
+
+// Alinea los cierres de cada símbolo sobre un calendario común, repitiendo el último cierre
+// en las fechas en que un instrumento no cotiza, y los normaliza a la fecha base.
+pub fn compare(workspace: &Workspace, settings: &ComparisonSettings) -> Option<Comparison> {
+    let closes: Vec<BTreeMap<Fecha, f64>> = workspace
+        .symbols
+        .iter()
+        .map(|symbol| {
+            symbol
+                .bars
+                .iter()
+                .filter(|c| c.close.is_finite())
+                .filter_map(|c| Fecha::parse(&c.date).map(|f| (f, c.close)))
+                .collect()
+        })
+        .collect();
+
+    let mut dates: Vec<Fecha> = closes.iter().flat_map(|m| m.keys().copied()).collect();
+    dates.sort();
+    dates.dedup();
+
+    let aligned: Vec<Vec<Option<f64>>> = closes
+        .iter()
+        .map(|m| dates.iter().map(|d| m.range(..=*d).next_back().map(|(_, v)| *v)).collect())
+        .collect();
+
+    let common_start = (0..dates.len()).find(|&i| aligned.iter().all(|s| s[i].is_some()))?;
+    let base_index = match settings.base_date {
+        Some(base) => dates.iter().position(|d| *d >= base).unwrap_or(dates.len() - 1).max(common_start),
+        None => common_start,
+    };
+
+    let series = workspace
+        .symbols
+        .iter()
+        .zip(&aligned)
+        .map(|(symbol, values)| {
+            let base_value = values[base_index].unwrap_or(f64::NAN);
+            let points = values
+                .iter()
+                .enumerate()
+                .filter_map(|(i, v)| v.map(|v| (i as f64, normalize(v, base_value, settings.normalization))))
+                .collect();
+            (symbol.name.clone(), points)
+        })
+        .collect();
+
+    Some(Comparison {
+        base: dates[base_index],
+        dates,
+        series,
+    })
+}
+
+// Mueve la fecha base `delta` fechas dentro del calendario común
+pub fn shift_base_date(workspace: &Workspace, settings: &mut ComparisonSettings, delta: isize) {
+    if let Some(comparison) = workspace.comparison(settings).as_ref() {
+        let index = comparison.dates.iter().position(|d| *d == comparison.base).unwrap_or(0);
+        let target = index.saturating_add_signed(delta).min(comparison.dates.len() - 1);
+        settings.base_date = Some(comparison.dates[target]);
+    }
+}
+
+fn normalize(value: f64, base: f64, normalization: Normalization) -> f64 {
+    match normalization {
+        Normalization::Base100 => value / base * 100.0,
+        Normalization::Percent => (value / base - 1.0) * 100.0,
+    }
+}
@@ -0,0 +1,111 @@
+use std::error::Error;
+
+use crate::ajustes::FillMethod;
+use crate::alerts::Webhook;
+use crate::fechas::Fecha;
+use crate::serde_data::ColumnMapping;
+use crate::stream::StreamSource;
+
+// Opciones de línea de comandos
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    // Ficheros o directorios a cargar, uno por instrumento
+    pub file_paths: Vec<String>,
+    pub strict: bool,
+    pub fill: Option<FillMethod>,
+    pub adjustments: Option<String>,
+    // Sin mapeo explícito se detecta por la cabecera del fichero
+    pub mapping: Option<ColumnMapping>,
+    // Fecha base del modo comparación
+    pub base_date: Option<Fecha>,
+    // Ventana de la correlación y beta móviles
+    pub correlation_window: Option<usize>,
+    // Días por barra de la temporalidad de N días
+    pub ndays: Option<u32>,
+    // Recargar los ficheros cuando cambian en disco
+    pub watch: bool,
+    // Arrancar en reproducción histórica desde esta fecha
+    pub replay_start: Option<Fecha>,
+    // Barras en tiempo real que se añaden al primer instrumento
+    pub stream: Option<StreamSource>,
+    // CSV con las reglas de alerta
+    pub alerts: Option<String>,
+    // Fichero donde se anotan las alertas disparadas
+    pub alert_log: Option<String>,
+    pub webhook: Option<Webhook>,
+    // Capital inicial y salidas, en %, del backtest
+    pub capital: Option<f64>,
+    pub stop_loss: Option<f64>,
+    pub take_profit: Option<f64>,
+}
+
+impl Config {
+    pub fn from_args(args: impl Iterator<Item = String>) -> Result<Self, Box<dyn Error>> {
+        let mut config = Config {
+            watch: true,
+            ..Config::default()
+        };
+        let mut args = args.skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--strict" => config.strict = true,
+                "--no-watch" => config.watch = false,
+                "--fill" => {
+                    let value = next_value(&mut args, &arg)?;
+                    config.fill = Some(
+                        FillMethod::parse(&value)
+                            .ok_or(format!("Método de relleno desconocido: {}", value))?,
+                    );
+                }
+                "--adjustments" => config.adjustments = Some(next_value(&mut args, &arg)?),
+                "--mapping" => {
+                    let value = next_value(&mut args, &arg)?;
+                    config.mapping = Some(ColumnMapping::from_name_or_file(&value)?);
+                }
+                "--base-date" => {
+                    let value = next_value(&mut args, &arg)?;
+                    config.base_date =
+                        Some(Fecha::parse(&value).ok_or(format!("Fecha base inválida: {}", value))?);
+                }
+                "--replay" => {
+                    let value = next_value(&mut args, &arg)?;
+                    config.replay_start =
+                        Some(Fecha::parse(&value).ok_or(format!("Fecha de replay inválida: {}", value))?);
+                }
+                "--stream" => {
+                    let value = next_value(&mut args, &arg)?;
+                    config.stream =
+                        Some(StreamSource::parse(&value).ok_or(format!("Origen de datos desconocido: {}", value))?);
+                }
+                "--alerts" => config.alerts = Some(next_value(&mut args, &arg)?),
+                "--alert-log" => config.alert_log = Some(next_value(&mut args, &arg)?),
+                "--webhook" => {
+                    let value = next_value(&mut args, &arg)?;
+                    config.webhook = Some(Webhook::parse(&value).ok_or(format!("URL de webhook inválida: {}", value))?);
+                }
+                "--capital" => config.capital = Some(next_value(&mut args, &arg)?.parse()?),
+                "--stop-loss" => config.stop_loss = Some(next_value(&mut args, &arg)?.parse()?),
+                "--take-profit" => config.take_profit = Some(next_value(&mut args, &arg)?.parse()?),
+                "--corr-window" => config.correlation_window = Some(next_value(&mut args, &arg)?.parse()?),
+                "--ndays" => {
+                    let value = next_value(&mut args, &arg)?;
+                    let ndays = value.parse().ok().filter(|n| *n >= 2);
+                    config.ndays = Some(ndays.ok_or(format!("Número de días inválido (mínimo 2): {}", value))?);
+                }
+                _ if arg.starts_with("--") => return Err(format!("Opción desconocida: {}", arg).into()),
+                _ => config.file_paths.push(arg),
+            }
+        }
+
+        if config.file_paths.is_empty() {
+            config.file_paths.push("data.csv".to_string());
+        }
+
+        Ok(config)
+    }
+}
+
+fn next_value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, Box<dyn Error>> {
+    args.next().ok_or_else(|| format!("Falta el valor de {}", flag).into())
+}
@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+
+use crate::fechas::Fecha;
+use crate::workspace::{Symbol, Workspace};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CorrelationMethod {
+    #[default]
+    Pearson,
+    Spearman,
+}
+
+impl CorrelationMethod {
+    pub fn next(&self) -> Self {
+        match self {
+            CorrelationMethod::Pearson => CorrelationMethod::Spearman,
+            CorrelationMethod::Spearman => CorrelationMethod::Pearson,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            CorrelationMethod::Pearson => "Pearson",
+            CorrelationMethod::Spearman => "Spearman",
+        }
+    }
+
+    pub fn apply(&self, x: &[f64], y: &[f64]) -> f64 {
+        match self {
+            CorrelationMethod::Pearson => pearson(x, y),
+            CorrelationMethod::Spearman => spearman(x, y),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CorrelationSettings {
+    pub method: CorrelationMethod,
+    // Número de retornos de cada ventana móvil
+    pub window: usize,
+    // Índice del símbolo de referencia para la correlación móvil y la beta
+    pub benchmark: usize,
+}
+
+impl Default for CorrelationSettings {
+    fn default() -> Self {
+        CorrelationSettings {
+            method: CorrelationMethod::Pearson,
+            window: 30,
+            benchmark: 1,
+        }
+    }
+}
+
+pub fn pearson(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len().min(y.len());
+    if n < 2 {
+        return f64::NAN;
+    }
+    let mean_x = x[..n].iter().sum::<f64>() / n as f64;
+    let mean_y = y[..n].iter().sum::<f64>() / n as f64;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for i in 0..n {
+        let (dx, dy) = (x[i] - mean_x, y[i] - mean_y);
+        cov += dx * dy;
+        var_x += dx * dx;
+        var_y += dy * dy;
+    }
+    cov / (var_x * var_y).sqrt()
+}
+
+// Pearson sobre los rangos, con rango medio para los empates
+pub fn spearman(x: &[f64], y: &[f64]) -> f64 {
+    pearson(&ranks(x), &ranks(y))
+}
+
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && values[order[j + 1]] == values[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        for k in i..=j {
+            ranks[order[k]] = rank;
+        }
+        i = j + 1;
+    }
+    ranks
+}
+
+// Sensibilidad de `asset` frente a `benchmark`: cov(asset, benchmark) / var(benchmark)
+pub fn beta(asset: &[f64], benchmark: &[f64]) -> f64 {
+    let n = asset.len().min(benchmark.len());
+    if n < 2 {
+        return f64::NAN;
+    }
+    let mean_a = asset[..n].iter().sum::<f64>() / n as f64;
+    let mean_b = benchmark[..n].iter().sum::<f64>() / n as f64;
+    let (mut cov, mut var_b) = (0.0, 0.0);
+    for i in 0..n {
+        cov += (asset[i] - mean_a) * (benchmark[i] - mean_b);
+        var_b += (benchmark[i] - mean_b).powi(2);
+    }
+    cov / var_b
+}
+
+// Retornos diarios de dos símbolos sobre las fechas en que ambos cotizan
+pub fn aligned_returns(a: &Symbol, b: &Symbol) -> (Vec<Fecha>, Vec<f64>, Vec<f64>) {
+    let closes_b = daily_closes(b);
+    let common: Vec<(Fecha, f64, f64)> = daily_closes(a)
+        .into_iter()
+        .filter_map(|(fecha, close_a)| closes_b.get(&fecha).map(|close_b| (fecha, close_a, *close_b)))
+        .collect();
+
+    let mut dates = Vec::new();
+    let mut returns_a = Vec::new();
+    let mut returns_b = Vec::new();
+    for pair in common.windows(2) {
+        dates.push(pair[1].0);
+        returns_a.push(pair[1].1 / pair[0].1 - 1.0);
+        returns_b.push(pair[1].2 / pair[0].2 - 1.0);
+    }
+    (dates, returns_a, returns_b)
+}
+
+fn daily_closes(symbol: &Symbol) -> BTreeMap<Fecha, f64> {
+    symbol
+        .daily_bars
+        .iter()
+        .filter(|c| c.close.is_finite() && c.close > 0.0 && !c.gap)
+        .filter_map(|c| Fecha::parse(&c.date).map(|f| (f, c.close)))
+        .collect()
+}
+
+// Correlación y beta sobre ventanas móviles de `window` retornos, etiquetadas con la última fecha
+pub fn rolling(
+    asset: &Symbol,
+    benchmark: &Symbol,
+    settings: &CorrelationSettings,
+) -> Vec<(Fecha, f64, f64)> {
+    let (dates, returns_a, returns_b) = aligned_returns(asset, benchmark);
+    let window = settings.window.max(2);
+    if returns_a.len() < window {
+        return Vec::new();
+    }
+
+    (window..=returns_a.len())
+        .map(|end| {
+            let a = &returns_a[end - window..end];
+            let b = &returns_b[end - window..end];
+            (dates[end - 1], settings.method.apply(a, b), beta(a, b))
+        })
+        .collect()
+}
+
+// Matriz de correlación de retornos diarios entre todos los símbolos cargados
+pub fn correlation_matrix(workspace: &Workspace, method: CorrelationMethod) -> Vec<Vec<f64>> {
+    let symbols = &workspace.symbols;
+    symbols
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            symbols
+                .iter()
+                .enumerate()
+                .map(|(j, b)| {
+                    if i == j {
+                        return 1.0;
+                    }
+                    let (_, returns_a, returns_b) = aligned_returns(a, b);
+                    method.apply(&returns_a, &returns_b)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn export_matrix_csv(
+    file_path: &str,
+    workspace: &Workspace,
+    matrix: &[Vec<f64>],
+) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_path(file_path)?;
+    let names: Vec<&str> = workspace.symbols.iter().map(|s| s.name.as_str()).collect();
+
+    let mut header = vec![""];
+    header.extend(&names);
+    wtr.write_record(&header)?;
+    for (name, row) in names.iter().zip(matrix) {
+        let mut record = vec![name.to_string()];
+        record.extend(row.iter().map(|v| format!("{:.4}", v)));
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
@@ -4,20 +4,30 @@ use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     prelude::*,
     style::{Color, Style, Modifier},
-    text::{Span, Spans, Text, Line},
-    widgets::{Block, Borders, Dataset, Paragraph, canvas::{Canvas, Context, Line as CanvasLine, Rectangle as CanvasRectangle}, Chart, Axis},
+    text::{Span, Line},
+    widgets::{Block, Borders, Cell, Dataset, GraphType, Paragraph, Row, Table, canvas::{Canvas, Context, Line as CanvasLine, Points, Rectangle as CanvasRectangle}, Chart, Axis},
     Frame,
 };
+use std::ops::Range;
 //mod predicion;
-use crate::predicion;
-use crate::predicion::*;
-use crate::predicion::calculate_rsi;
-use crate::predicion::calculate_macd;
-use crate::predicion::calculate_bollinger_bands;
-use crate::predicion::calculate_momentum;
-use crate::predicion::calculate_sma;
-
-#[derive(Debug, Clone, PartialEq)]
+use crate::drawings::{sidecar_path, Anchor, Drawing, Sketch, Tool};
+use crate::fechas::Fecha;
+use crate::indicadores::{Indicator, IndicatorSet, IndicatorValues};
+use crate::overlays::Overlay;
+use crate::panes::{Pane, PaneStack};
+use crate::predicion::VolumeProfile;
+use crate::replay::Replay;
+use crate::resample::Timeframe;
+use crate::signals::{Side, Signal};
+use crate::viewport::Viewport;
+use crate::workspace::{Symbol, Workspace};
+use crate::axis::{AxisDomain, LABELS};
+use crate::backtest::{BacktestResult, BacktestSettings};
+use crate::comparacion::{ComparisonSettings, Normalization};
+use crate::correlacion::CorrelationSettings;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ChartType {
     Candlestick,
     Line,
@@ -28,6 +38,9 @@ pub enum ChartType {
     RSI,
     BollingerBands,
     Momentum,
+    Comparison,
+    Correlation,
+    Backtest,
 }
 
 impl ChartType {
@@ -41,8 +54,40 @@ impl ChartType {
             ChartType::SMA => ChartType::RSI,
             ChartType::RSI => ChartType::BollingerBands,
             ChartType::BollingerBands => ChartType::Momentum,
-            ChartType::Momentum => ChartType::Candlestick,
+            ChartType::Momentum => ChartType::Comparison,
+            ChartType::Comparison => ChartType::Correlation,
+            ChartType::Correlation => ChartType::Backtest,
+            ChartType::Backtest => ChartType::Candlestick,
+        }
+    }
+
+    // Vistas que dibujan el precio y llevan perfil de volumen
+    pub fn is_price_view(&self) -> bool {
+        matches!(self, ChartType::Candlestick | ChartType::Line | ChartType::Dots | ChartType::Bars)
+    }
+
+    // Todas las vistas, en el orden en que las recorre `next`
+    pub fn all() -> Vec<Self> {
+        let mut all = vec![ChartType::Candlestick];
+        while let Some(next) = all.last().map(ChartType::next).filter(|t| *t != ChartType::Candlestick) {
+            all.push(next);
         }
+        all
+    }
+
+    // Vistas que dibujan un indicador en su propio eje, con el precio en un eje secundario
+    pub fn indicator_pane(&self) -> Option<Pane> {
+        match self {
+            ChartType::MACD => Some(Pane::Macd),
+            ChartType::RSI => Some(Pane::Rsi),
+            ChartType::Momentum => Some(Pane::Momentum),
+            _ => None,
+        }
+    }
+
+    // Vistas cuyo eje horizontal son las barras del símbolo seleccionado
+    pub fn shows_bars(&self) -> bool {
+        !matches!(self, ChartType::Comparison | ChartType::Correlation | ChartType::Backtest)
     }
 
     fn as_str(&self) -> &str {
@@ -56,32 +101,107 @@ impl ChartType {
             ChartType::RSI => "RSI",
             ChartType::BollingerBands => "Bollinger Bands",
             ChartType::Momentum => "Momentum",
+            ChartType::Comparison => "Comparación",
+            ChartType::Correlation => "Correlación",
+            ChartType::Backtest => "Backtest",
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Candle {
     pub date: String,
     pub open: f64,
     pub high: f64,
     pub low: f64,
     pub close: f64,
+    pub volume: f64,
+    // Barra sintética que rellena un hueco de los datos
+    pub gap: bool,
 }
 
-pub fn draw_chart<B: Backend>(
-    f: &mut Frame<B>,
-    data: &Vec<(String, f64)>,
-    prediction_value: f64,
-    chart_type: &ChartType,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let size = f.size();
+// Estado de la vista que se conserva al cambiar de símbolo
+#[derive(Debug, Clone)]
+pub struct ViewState {
+    pub chart_type: ChartType,
+    pub timeframe: Timeframe,
+    // Días por barra de la temporalidad de N días por la que pasa R
+    pub ndays: u32,
+    pub comparison: ComparisonSettings,
+    pub correlation: CorrelationSettings,
+    pub backtest: BacktestSettings,
+    // Último aviso mostrado en el panel de controles (recargas, exportaciones...)
+    pub status: Option<String>,
+    // Reproducción histórica en curso
+    pub replay: Option<Replay>,
+    pub viewport: Viewport,
+    // Barra seleccionada con el cursor, como índice en `bars` del símbolo
+    pub cursor: Option<usize>,
+    pub panes: PaneStack,
+    // Superposiciones activas en las vistas de precio
+    pub overlays: Vec<Overlay>,
+    // Eje de precio en escala logarítmica
+    pub log_scale: bool,
+    // Herramienta de dibujo activa y puntos ya marcados
+    pub sketch: Option<Sketch>,
+    // Alertas disparadas pendientes de descartar, de la más antigua a la más reciente
+    pub notifications: Vec<String>,
+    // Marcas de compra y venta en el precio y panel con la lista de señales
+    pub signals: bool,
+    // Primera señal mostrada en el panel, contando desde la más reciente
+    pub signals_scroll: usize,
+}
+
+impl Default for ViewState {
+    fn default() -> Self {
+        ViewState {
+            chart_type: ChartType::Line,
+            timeframe: Timeframe::Daily,
+            ndays: 3,
+            comparison: ComparisonSettings::default(),
+            correlation: CorrelationSettings::default(),
+            backtest: BacktestSettings::default(),
+            status: None,
+            replay: None,
+            viewport: Viewport::default(),
+            cursor: None,
+            panes: PaneStack::default(),
+            overlays: Vec::new(),
+            log_scale: false,
+            sketch: None,
+            notifications: Vec::new(),
+            signals: false,
+            signals_scroll: 0,
+        }
+    }
+}
 
+// Zonas de la pantalla; también sirven para localizar los clics del ratón
+#[derive(Debug, Clone)]
+pub struct ChartAreas {
+    pub symbols: Option<Rect>,
+    pub info: Rect,
+    // Lista de señales bajo las estadísticas, si está activada
+    pub signals: Option<Rect>,
+    pub chart: Rect,
+    // Sólo en las vistas de precio
+    pub profile: Option<Rect>,
+    // Subpaneles de indicadores bajo el precio, en el orden de `view.panes`
+    pub panes: Vec<Rect>,
+    // Barra de notificaciones, sólo con alertas pendientes
+    pub notifications: Option<Rect>,
+    pub controls: Rect,
+}
+
+pub fn chart_layout(size: Rect, workspace: &Workspace, view: &ViewState) -> ChartAreas {
+    let chart_type = &view.chart_type;
+    let notifications_height = if view.notifications.is_empty() { 0 } else { 1 };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Min(3),
-            Constraint::Length(3),
+            Constraint::Length(notifications_height),
+            Constraint::Length(4),
         ])
         .split(size);
 
@@ -93,57 +213,330 @@ pub fn draw_chart<B: Backend>(
         ])
         .split(chunks[0]);
 
-    let info = create_info_panel(data);
-    f.render_widget(info, upper_chunks[0]);
+    // Con varios instrumentos cargados, la lista de símbolos va encima de las estadísticas
+    let (symbols, info) = if workspace.symbols.len() > 1 {
+        let list_height = (workspace.symbols.len() as u16 + 2).min(upper_chunks[0].height / 2);
+        let left_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(list_height),
+                Constraint::Min(3),
+            ])
+            .split(upper_chunks[0]);
+        (Some(left_chunks[0]), left_chunks[1])
+    } else {
+        (None, upper_chunks[0])
+    };
+
+    // La lista de señales comparte la columna con las estadísticas
+    let (info, signals) = if view.signals {
+        let info_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(50),
+                Constraint::Percentage(50),
+            ])
+            .split(info);
+        (info_chunks[0], Some(info_chunks[1]))
+    } else {
+        (info, None)
+    };
+
+    // Las vistas de precio llevan el perfil de volumen a la derecha
+    let (chart, profile) = if chart_type.is_price_view() {
+        let price_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(80),
+                Constraint::Percentage(20),
+            ])
+            .split(upper_chunks[1]);
+        (price_chunks[0], Some(price_chunks[1]))
+    } else {
+        (upper_chunks[1], None)
+    };
+
+    // El precio ocupa el doble de alto que cada subpanel
+    let (chart, panes) = if chart_type.is_price_view() && !view.panes.panes.is_empty() {
+        let parts = view.panes.panes.len() as u32 + 2;
+        let constraints: Vec<Constraint> = std::iter::once(Constraint::Ratio(2, parts))
+            .chain(view.panes.panes.iter().map(|_| Constraint::Ratio(1, parts)))
+            .collect();
+        let pane_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(chart);
+        (pane_chunks[0], pane_chunks[1..].to_vec())
+    } else {
+        (chart, Vec::new())
+    };
+
+    ChartAreas {
+        symbols,
+        info,
+        signals,
+        chart,
+        profile,
+        panes,
+        notifications: (notifications_height > 0).then_some(chunks[1]),
+        controls: chunks[2],
+    }
+}
+
+impl ViewState {
+    // Muestra u oculta una superposición; se dibujan en el orden en que se activaron
+    pub fn toggle_overlay(&mut self, overlay: Overlay) {
+        match self.overlays.iter().position(|o| *o == overlay) {
+            Some(position) => {
+                self.overlays.remove(position);
+            }
+            None => self.overlays.push(overlay),
+        }
+    }
+
+    // Activa el cursor en la última barra visible, o lo oculta
+    pub fn toggle_cursor(&mut self, workspace: &Workspace) {
+        let bars = &workspace.current().bars;
+        self.cursor = match self.cursor {
+            Some(_) => None,
+            None => self.viewport.range(bars).end.checked_sub(1),
+        };
+    }
+
+    // Mueve el cursor `delta` barras, desplazando la ventana si se sale de ella
+    pub fn move_cursor(&mut self, workspace: &Workspace, delta: isize) {
+        let bars = &workspace.current().bars;
+        if let Some(index) = self.cursor {
+            let index = index.saturating_add_signed(delta).min(bars.len().saturating_sub(1));
+            self.cursor = Some(index);
+            self.viewport.ensure_visible(bars, index);
+        }
+    }
+
+    // Pasa a la siguiente herramienta de dibujo; tras la última se sale del modo de dibujo
+    pub fn cycle_tool(&mut self) {
+        self.sketch = Tool::next(self.sketch.as_ref().map(|s| s.tool)).map(Sketch::new);
+    }
+
+    pub fn is_typing_note(&self) -> bool {
+        self.sketch.as_ref().is_some_and(|s| s.text.is_some())
+    }
+
+    // Punto de dibujo en la barra del cursor, a su cierre
+    pub fn cursor_anchor(&self, workspace: &Workspace) -> Option<Anchor> {
+        let bar = workspace.current().bars.get(self.cursor?)?;
+        Some(Anchor { fecha: bar.date.clone(), precio: bar.close })
+    }
+
+    // Marca un punto del dibujo en curso y lo guarda si queda completo
+    pub fn place_point(&mut self, workspace: &mut Workspace, anchor: Anchor) {
+        if let Some(drawing) = self.sketch.as_mut().and_then(|s| s.add_point(anchor)) {
+            self.save_drawing(workspace, drawing);
+        }
+    }
+
+    pub fn finish_note(&mut self, workspace: &mut Workspace) {
+        if let Some(drawing) = self.sketch.as_mut().and_then(Sketch::finish_text) {
+            self.save_drawing(workspace, drawing);
+        }
+    }
+
+    fn save_drawing(&mut self, workspace: &mut Workspace, drawing: Drawing) {
+        let symbol = workspace.current_mut();
+        self.status = Some(match symbol.add_drawing(drawing) {
+            Ok(()) => format!("Dibujo guardado en {}", sidecar_path(&symbol.file_path).display()),
+            Err(e) => format!("No se pudo guardar el dibujo: {}", e),
+        });
+    }
+
+    pub fn remove_last_drawing(&mut self, workspace: &mut Workspace) {
+        self.status = match workspace.current_mut().remove_last_drawing() {
+            Ok(Some(_)) => Some("Último dibujo borrado".to_string()),
+            Ok(None) => Some("No hay dibujos que borrar".to_string()),
+            Err(e) => Some(format!("No se pudieron guardar los dibujos: {}", e)),
+        };
+    }
+
+    // Desplaza la lista de señales; `delta` positivo baja hacia las más antiguas
+    pub fn scroll_signals(&mut self, workspace: &Workspace, delta: isize) {
+        let len = workspace.current().signals().len();
+        self.signals_scroll = self.signals_scroll.saturating_add_signed(delta).min(len.saturating_sub(1));
+    }
+
+    pub fn cursor_date(&self, workspace: &Workspace) -> Option<Fecha> {
+        let index = self.cursor?;
+        workspace.current().bars.get(index).and_then(|c| Fecha::parse(&c.date))
+    }
+
+    // Recoloca el cursor en la barra que contiene `date` tras cambiar de símbolo o temporalidad
+    pub fn set_cursor_date(&mut self, workspace: &Workspace, date: Option<Fecha>) {
+        if let Some(date) = date {
+            let bars = &workspace.current().bars;
+            let index = bars
+                .partition_point(|c| Fecha::parse(&c.date).is_none_or(|f| f <= date))
+                .saturating_sub(1);
+            self.cursor = Some(index);
+            self.viewport.ensure_visible(bars, index);
+        }
+    }
+}
+
+pub fn draw_chart<B: Backend>(
+    f: &mut Frame<B>,
+    workspace: &Workspace,
+    _prediction_value: f64,
+    view: &ViewState,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let chart_type = &view.chart_type;
+    let areas = chart_layout(f.size(), workspace, view);
+
+    // Los gráficos de la serie sólo dibujan la ventana de barras visible
+    let symbol = workspace.current();
+    let range = view.viewport.range(&symbol.bars);
+    let data = &symbol.chart_data[range.clone()];
+    let bars = &symbol.bars[range.clone()];
+    let cursor = cursor_in_range(view, &range);
+    let scale = view_scale(view, symbol, range.clone());
+    let bounds = scale.bounds;
+    // Superposiciones, cursor y subpaneles usan las mismas columnas que el trazado principal
+    let plot = plot_area(chart_type, areas.chart, data, &scale);
+
+    if let Some(symbols_area) = areas.symbols {
+        f.render_widget(create_symbol_list(workspace), symbols_area);
+    }
+
+    // El backtest recorre la serie completa; su resumen y sus operaciones ocupan el panel de estadísticas
+    let backtest = (*chart_type == ChartType::Backtest).then(|| symbol.backtest(&view.backtest));
+
+    // Con el cursor activo, las estadísticas pasan a describir la barra seleccionada
+    let info = match (&backtest, cursor) {
+        (Some(result), _) => create_backtest_panel(symbol, result),
+        (None, Some(index)) => create_bar_panel(symbol, index),
+        (None, None) => create_info_panel(&symbol.indicators),
+    };
+    f.render_widget(info, areas.info);
+    if let Some(signals_area) = areas.signals {
+        f.render_widget(create_signals_panel(symbol, view.signals_scroll, &range), signals_area);
+    }
 
+    let profile_area = areas.profile.unwrap_or_default();
+    // Los títulos de las vistas de indicador dan el valor en la última barra visible, el mismo
+    // que cierra la línea
+    let history = symbol.indicator_history();
+    let last_values = history[..range.end].last();
     match chart_type {
         ChartType::Candlestick => {
-            let candles = convert_to_candles(data, 24);
-            draw_candlestick_view(f, &candles, upper_chunks[1])?;
+            draw_candlestick_view(f, bars, &scale, plot, areas.chart)?;
+            draw_volume_profile(f, symbol, range.clone(), &scale, plot, profile_area)?;
         },
         ChartType::Line => {
-            draw_line_view(f, data, upper_chunks[1])?;
+            draw_line_view(f, "Gráfico de Línea", data, &scale, plot, areas.chart)?;
+            draw_volume_profile(f, symbol, range.clone(), &scale, plot, profile_area)?;
         },
         ChartType::Dots => {
-            draw_dots_view(f, data, upper_chunks[1])?;
+            draw_dots_view(f, data, &scale, plot, areas.chart)?;
+            draw_volume_profile(f, symbol, range.clone(), &scale, plot, profile_area)?;
         },
         ChartType::Bars => {
-            draw_bars_view(f, data, upper_chunks[1])?;
+            draw_bars_view(f, data, &scale, plot, areas.chart)?;
+            draw_volume_profile(f, symbol, range.clone(), &scale, plot, profile_area)?;
         },
         ChartType::MACD => {
-            draw_macd_view(f, data, upper_chunks[1])?;
+            let (macd_line, signal_line) = last_values.map_or((f64::NAN, f64::NAN), |v| v.macd);
+            let title = format!("Gráfico de MACD {:.2} / Señal {:.2}", macd_line, signal_line);
+            draw_indicator_view(f, &title, &scale, data, plot, areas.chart)?;
         },
         ChartType::SMA => {
-            draw_sma_view(f, data, upper_chunks[1])?;
+            draw_line_view(f, "Gráfico de SMA", data, &scale, plot, areas.chart)?;
         },
         ChartType::RSI => {
-            draw_rsi_view(f, data, upper_chunks[1])?;
+            let title = format!("Gráfico de RSI (14): {:.2}", last_values.map_or(f64::NAN, |v| v.rsi));
+            draw_indicator_view(f, &title, &scale, data, plot, areas.chart)?;
         },
         ChartType::BollingerBands => {
-            draw_bollinger_bands_view(f, data, upper_chunks[1])?;
+            draw_line_view(f, "Gráfico de Bollinger Bands", data, &scale, plot, areas.chart)?;
         },
         ChartType::Momentum => {
-            draw_momentum_view(f, data, upper_chunks[1])?;
+            let title = format!("Gráfico de Momentum (14): {:.2}%", last_values.map_or(f64::NAN, |v| v.momentum));
+            draw_indicator_view(f, &title, &scale, data, plot, areas.chart)?;
+        },
+        ChartType::Comparison => {
+            draw_comparison_view(f, workspace, &view.comparison, areas.chart)?;
+        },
+        ChartType::Correlation => {
+            draw_correlation_view(f, workspace, &view.correlation, areas.chart)?;
+        },
+        ChartType::Backtest => {
+            if let Some(result) = &backtest {
+                draw_backtest_view(f, symbol, result, areas.chart)?;
+            }
         },
     }
 
-    let controls = create_control_panel(chart_type);
-    f.render_widget(controls, chunks[1]);
+    if !scale.overlays.is_empty() {
+        let candles = *chart_type == ChartType::Candlestick;
+        let (price, other): (Vec<_>, Vec<_>) =
+            scale.overlays.iter().partition(|(overlay, _)| overlay.domain() == AxisDomain::Price);
+        draw_overlays(f, &price, candles, bounds, plot);
+        if let Some((_, secondary)) = scale.secondary {
+            draw_overlays(f, &other, candles, secondary, plot);
+        }
+    }
+    // Los dibujos están en precio, así que sólo van en las vistas con el eje de precio
+    if chart_type.shows_bars() && scale.lines.is_empty() {
+        let candles = *chart_type == ChartType::Candlestick;
+        draw_drawings(f, symbol, view.sketch.as_ref(), range.clone(), &scale, candles, plot);
+        if view.signals {
+            draw_signal_markers(f, symbol, range.clone(), &scale, candles, plot);
+        }
+    }
+    if chart_type.shows_bars() {
+        draw_legend(f, &legend_entries(chart_type, &scale), plot);
+    }
+    if let (Some(index), true) = (cursor, chart_type.shows_bars()) {
+        let index = index - range.start;
+        // En las vistas de indicador la línea horizontal marca el valor del indicador
+        let level = match scale.lines.first() {
+            Some((_, values)) => values[index],
+            None => scale.domain.coordinate(bars[index].close),
+        };
+        draw_crosshair(f, chart_type, plot, bounds, level, index, bars.len());
+    }
+
+    for (position, (pane, area)) in view.panes.panes.iter().zip(&areas.panes).enumerate() {
+        let pane_view = PaneView {
+            pane: *pane,
+            bars,
+            values: &history[range.clone()],
+            cursor: cursor.map(|index| index - range.start),
+            candles: *chart_type == ChartType::Candlestick,
+            selected: position == view.panes.selected,
+        };
+        draw_pane(f, &pane_view, *area, plot);
+    }
+
+    if let Some(notifications_area) = areas.notifications {
+        f.render_widget(create_notification_bar(&view.notifications), notifications_area);
+    }
+
+    let window = view.viewport.window(&symbol.bars);
+    let controls = create_control_panel(view, workspace.symbols.len() > 1, window);
+    f.render_widget(controls, areas.controls);
 
     Ok(())
 }
 
-pub fn create_info_panel(data: &Vec<(String, f64)>) -> Paragraph<'static> {
-    let max_value = data.iter().map(|(_, v)| v).fold(f64::NEG_INFINITY, |a, &b| a.max(b));
-    let min_value = data.iter().map(|(_, v)| v).fold(f64::INFINITY, |a, &b| a.min(b));
-    let avg_value = data.iter().map(|(_, v)| v).sum::<f64>() / data.len() as f64;
-    let last_value = data.last().map(|(_, v)| *v).unwrap_or(0.0);
-// Calcular indicadores técnicos
-    let rsi = predicion::calculate_rsi(data, 14);
-    let (macd_line, signal_line) = predicion::calculate_macd(data);
-    let (upper_band, middle_band, lower_band) = predicion::calculate_bollinger_bands(data, 20);
-    let momentum = predicion::calculate_momentum(data, 14);
-    let sma = predicion::calculate_sma(data, 20);
+pub fn create_info_panel(indicators: &IndicatorSet) -> Paragraph<'static> {
+    let max_value = indicators.stats.max;
+    let min_value = indicators.stats.min;
+    let avg_value = indicators.stats.average();
+    let last_value = indicators.stats.last;
+    // Indicadores técnicos, actualizados barra a barra al cargar o recibir datos
+    let rsi = indicators.rsi.value();
+    let (macd_line, signal_line) = indicators.macd.value();
+    let (upper_band, middle_band, lower_band) = indicators.bollinger.value();
+    let momentum = indicators.momentum.value();
+    let sma = indicators.sma.value();
     
     let info_text = vec![
         Line::from(vec![
@@ -206,16 +599,792 @@ Line::from(vec![
         .alignment(Alignment::Left)
 }
 
-fn create_control_panel(current_type: &ChartType) -> Paragraph<'static> {
-    let text = vec![
+// Índice del cursor dentro de la serie, si cae en la ventana visible
+fn cursor_in_range(view: &ViewState, range: &Range<usize>) -> Option<usize> {
+    view.cursor.filter(|index| range.contains(index))
+}
+
+// Zona de trazado de las vistas: el interior del bloque en el gráfico de velas y, en los
+// gráficos con ejes, lo que queda tras las etiquetas, repartido igual que hace ratatui
+fn plot_area(chart_type: &ChartType, area: Rect, data: &[(String, f64)], scale: &ViewScale) -> Rect {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    let inner = match scale.secondary {
+        Some((domain, bounds)) => secondary_axis_split(inner, domain, bounds).0,
+        None => inner,
+    };
+    if *chart_type == ChartType::Candlestick {
+        return inner;
+    }
+    let y_labels_width = scale
+        .domain
+        .labels(scale.bounds, inner.height.saturating_sub(2))
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or(0) as u16;
+    // La primera fecha del eje X acaba bajo el eje Y
+    let x_label_width = data.first().map_or(0, |(date, _)| date.chars().count() as u16).saturating_sub(1);
+    let left = y_labels_width.max(x_label_width).min(inner.width / 3) + 1;
+    Rect::new(
+        inner.x + left,
+        inner.y,
+        inner.width.saturating_sub(left),
+        inner.height.saturating_sub(2),
+    )
+}
+
+// Divide el interior de un gráfico entre el trazado y la columna del eje secundario derecho
+fn secondary_axis_split(inner: Rect, domain: AxisDomain, bounds: [f64; 2]) -> (Rect, Rect) {
+    let width = domain
+        .labels(bounds, inner.height)
+        .iter()
+        .map(|label| label.chars().count() as u16 + 1)
+        .max()
+        .unwrap_or(0)
+        .min(inner.width / 4);
+    let chart = Rect::new(inner.x, inner.y, inner.width - width, inner.height);
+    let axis = Rect::new(chart.right(), inner.y, width, inner.height);
+    (chart, axis)
+}
+
+// Líneas vertical y horizontal que marcan la barra seleccionada y su cierre
+fn draw_crosshair<B: Backend>(
+    f: &mut Frame<B>,
+    chart_type: &ChartType,
+    plot: Rect,
+    [y_min, y_max]: [f64; 2],
+    close: f64,
+    index: usize,
+    len: usize,
+) {
+    let x = if *chart_type == ChartType::Candlestick { index as f64 + 0.5 } else { index as f64 };
+    let x_max = len as f64;
+
+    // Sin bloque ni fondo: sólo se pintan las celdas de las líneas, encima del gráfico
+    let canvas = Canvas::default()
+        .x_bounds([0.0, x_max])
+        .y_bounds([y_min, y_max])
+        .paint(move |ctx| {
+            ctx.draw(&CanvasLine { x1: x, y1: y_min, x2: x, y2: y_max, color: Color::White });
+            ctx.draw(&CanvasLine { x1: 0.0, y1: close, x2: x_max, y2: close, color: Color::DarkGray });
+        });
+    f.render_widget(canvas, plot);
+}
+
+// Superposiciones de la vista: las elegidas en las vistas de precio y la propia en SMA y Bollinger
+fn view_overlays(view: &ViewState) -> Vec<Overlay> {
+    match view.chart_type {
+        ChartType::SMA => vec![Overlay::Sma(20)],
+        ChartType::BollingerBands => vec![Overlay::Bollinger(20)],
+        _ if view.chart_type.is_price_view() => view.overlays.clone(),
+        _ => Vec::new(),
+    }
+}
+
+// Superposición con los valores visibles de cada una de sus líneas
+type OverlayLines = (Overlay, Vec<Vec<f64>>);
+
+// Escala vertical de la vista. Las vistas de precio llevan sus superposiciones en el mismo eje,
+// lineal o logarítmico, salvo las de otro dominio (RSI), que van en un eje secundario; las de
+// indicador usan el dominio del indicador y dibujan el precio en el eje secundario. Los límites
+// y las superposiciones de precio están ya en coordenadas del eje.
+struct ViewScale {
+    domain: AxisDomain,
+    bounds: [f64; 2],
+    overlays: Vec<OverlayLines>,
+    // Líneas del indicador de la vista, con su nombre
+    lines: Vec<(&'static str, Vec<f64>)>,
+    guides: &'static [f64],
+    // Dominio y límites del eje derecho, donde va el precio en las vistas de indicador
+    secondary: Option<(AxisDomain, [f64; 2])>,
+}
+
+fn view_scale(view: &ViewState, symbol: &Symbol, range: Range<usize>) -> ViewScale {
+    let data = &symbol.chart_data[range.clone()];
+    if let Some(pane) = view.chart_type.indicator_pane() {
+        let lines = pane.lines(&symbol.indicator_history()[range]);
+        let values = lines.iter().flat_map(|(_, values)| values.iter().copied());
+        return ViewScale {
+            domain: pane.domain(),
+            bounds: pane.domain().bounds(values.chain(pane.guides().iter().copied())),
+            overlays: Vec::new(),
+            lines,
+            guides: pane.guides(),
+            secondary: Some((AxisDomain::Price, AxisDomain::Price.bounds(data.iter().map(|(_, v)| *v)))),
+        };
+    }
+
+    let domain = if view.log_scale { AxisDomain::LogPrice } else { AxisDomain::Price };
+    let mut overlays: Vec<OverlayLines> = view_overlays(view)
+        .into_iter()
+        .map(|overlay| (overlay, symbol.overlay(overlay, range.clone())))
+        .collect();
+    let on_price = |overlay: &Overlay| overlay.domain() == AxisDomain::Price;
+    for value in overlays.iter_mut().filter(|(o, _)| on_price(o)).flat_map(|(_, lines)| lines.iter_mut().flatten()) {
+        *value = domain.coordinate(*value);
+    }
+    // Las superposiciones de otro dominio comparten el eje derecho, con el dominio de la primera
+    let secondary = overlays.iter().map(|(o, _)| o.domain()).find(|d| *d != AxisDomain::Price).map(|secondary| {
+        let values = overlays.iter().filter(|(o, _)| !on_price(o)).flat_map(|(_, lines)| lines.iter().flatten());
+        (secondary, secondary.bounds(values.copied()))
+    });
+    let bars = &symbol.bars[range];
+    let mut bounds = match (&view.chart_type, domain) {
+        (ChartType::Candlestick, AxisDomain::Price) => candle_y_bounds(bars),
+        (ChartType::Candlestick, _) => domain.bounds(bars.iter().flat_map(|c| [c.low, c.high])),
+        _ => domain.bounds(data.iter().map(|(_, v)| *v)),
+    };
+    let price_values = overlays.iter().filter(|(o, _)| on_price(o)).flat_map(|(_, lines)| lines.iter().flatten());
+    for value in price_values.filter(|v| v.is_finite()) {
+        bounds = [bounds[0].min(*value), bounds[1].max(*value)];
+    }
+    ViewScale {
+        domain,
+        bounds,
+        overlays,
+        lines: Vec::new(),
+        guides: &[],
+        secondary,
+    }
+}
+
+// Color de cada línea de un indicador, en orden
+fn line_color(index: usize) -> Color {
+    [Color::Cyan, Color::Red, Color::Magenta][index % 3]
+}
+
+// Entradas de la leyenda: la serie principal y las superposiciones
+fn legend_entries(chart_type: &ChartType, scale: &ViewScale) -> Vec<(String, Color)> {
+    let mut entries: Vec<(String, Color)> = scale
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| (name.to_string(), line_color(i)))
+        .collect();
+    if !scale.lines.is_empty() {
+        entries.push(("Precio (eje derecho)".to_string(), Color::DarkGray));
+    } else if *chart_type != ChartType::Candlestick {
+        entries.push(("Precio".to_string(), Color::Cyan));
+    }
+    entries.extend(scale.overlays.iter().map(|(overlay, _)| {
+        let label = match overlay.domain() {
+            AxisDomain::Price => overlay.label(),
+            _ => format!("{} (eje derecho)", overlay.label()),
+        };
+        (label, overlay_color(*overlay))
+    }));
+    entries
+}
+
+fn overlay_color(overlay: Overlay) -> Color {
+    match overlay {
+        Overlay::Sma(20) => Color::Yellow,
+        Overlay::Sma(_) => Color::LightBlue,
+        Overlay::Ema(_) => Color::Magenta,
+        Overlay::Bollinger(_) => Color::Blue,
+        Overlay::Keltner(_) => Color::LightGreen,
+        Overlay::Sar => Color::LightRed,
+        Overlay::Vwap => Color::LightYellow,
+        Overlay::Rsi(_) => Color::LightMagenta,
+    }
+}
+
+// Dibuja las superposiciones encima del gráfico en la escala `bounds`, la del precio o la del
+// eje secundario
+fn draw_overlays<B: Backend>(
+    f: &mut Frame<B>,
+    overlays: &[&OverlayLines],
+    candles: bool,
+    bounds: [f64; 2],
+    plot: Rect,
+) {
+    let offset = if candles { 0.5 } else { 0.0 };
+    let len = overlays.first().and_then(|(_, lines)| lines.first()).map_or(0, Vec::len);
+    let canvas = Canvas::default()
+        .x_bounds([0.0, len as f64])
+        .y_bounds(bounds)
+        .paint(|ctx| {
+            for (overlay, lines) in overlays.iter().copied() {
+                let color = overlay_color(*overlay);
+                for line in lines {
+                    // El SAR se dibuja como puntos sueltos, que saltan al girar la tendencia
+                    if *overlay == Overlay::Sar {
+                        let coords: Vec<(f64, f64)> = line
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, v)| v.is_finite())
+                            .map(|(i, v)| (i as f64 + offset, *v))
+                            .collect();
+                        ctx.draw(&Points { coords: &coords, color });
+                        continue;
+                    }
+                    for (i, pair) in line.windows(2).enumerate() {
+                        if pair[0].is_finite() && pair[1].is_finite() {
+                            let x = i as f64 + offset;
+                            ctx.draw(&CanvasLine { x1: x, y1: pair[0], x2: x + 1.0, y2: pair[1], color });
+                        }
+                    }
+                }
+            }
+        });
+    f.render_widget(canvas, plot);
+}
+
+// Dibujos del símbolo y puntos ya marcados del dibujo en curso, en la escala de la vista
+fn draw_drawings<B: Backend>(
+    f: &mut Frame<B>,
+    symbol: &Symbol,
+    sketch: Option<&Sketch>,
+    range: Range<usize>,
+    scale: &ViewScale,
+    candles: bool,
+    plot: Rect,
+) {
+    let offset = if candles { 0.5 } else { 0.0 } - range.start as f64;
+    let x_bounds = [0.0, range.len() as f64];
+    let y = |price: f64| scale.domain.coordinate(price);
+    let point = |anchor: &Anchor| anchor.bar_index(&symbol.bars).map(|i| (i as f64 + offset, y(anchor.precio)));
+
+    let canvas = Canvas::default()
+        .x_bounds(x_bounds)
+        .y_bounds(scale.bounds)
+        .paint(|ctx| {
+            for drawing in &symbol.drawings {
+                match drawing {
+                    Drawing::Tendencia { desde, hasta } => {
+                        if let (Some(a), Some(b)) = (point(desde), point(hasta)) {
+                            draw_segment(ctx, a, b, x_bounds, scale.bounds, Color::Yellow);
+                        }
+                    }
+                    Drawing::Nivel { precio } => {
+                        let level = y(*precio);
+                        draw_segment(ctx, (0.0, level), (x_bounds[1], level), x_bounds, scale.bounds, Color::LightMagenta);
+                        ctx.print(0.0, level, Line::styled(format!("${:.2}", precio), Style::default().fg(Color::LightMagenta)));
+                    }
+                    Drawing::Rectangulo { desde, hasta } => {
+                        if let (Some((x1, y1)), Some((x2, y2))) = (point(desde), point(hasta)) {
+                            let corners = [(x1, y1), (x2, y1), (x2, y2), (x1, y2), (x1, y1)];
+                            for pair in corners.windows(2) {
+                                draw_segment(ctx, pair[0], pair[1], x_bounds, scale.bounds, Color::LightBlue);
+                            }
+                        }
+                    }
+                    Drawing::Nota { en, texto } => {
+                        if let Some((x, y)) = point(en) {
+                            // Cerca del borde derecho el texto acaba en el punto en vez de empezar en él
+                            let label = format!("✎ {}", texto);
+                            let width = label.chars().count() as f64 * x_bounds[1] / plot.width.max(1) as f64;
+                            let (x, label) = if x + width > x_bounds[1] {
+                                ((x - width).max(0.0), format!("{} ✎", texto))
+                            } else {
+                                (x, label)
+                            };
+                            ctx.print(x, y, Line::styled(label, Style::default().fg(Color::White)));
+                        }
+                    }
+                }
+            }
+            for (x, y) in sketch.iter().flat_map(|s| &s.points).filter_map(point) {
+                ctx.print(x, y, Line::styled("✚", Style::default().fg(Color::Yellow)));
+            }
+        });
+    f.render_widget(canvas, plot);
+}
+
+// Marcas ▲ bajo las barras con señal de compra y ▼ sobre las de venta
+fn draw_signal_markers<B: Backend>(
+    f: &mut Frame<B>,
+    symbol: &Symbol,
+    range: Range<usize>,
+    scale: &ViewScale,
+    candles: bool,
+    plot: Rect,
+) {
+    let signals = symbol.signals();
+    let offset = if candles { 0.5 } else { 0.0 };
+    let [low, high] = scale.bounds;
+    // Una fila de separación para no tapar la barra
+    let row = (high - low) / plot.height.max(1) as f64;
+
+    let canvas = Canvas::default()
+        .x_bounds([0.0, range.len() as f64])
+        .y_bounds(scale.bounds)
+        .paint(|ctx| {
+            for signal in signals.iter().filter(|s| range.contains(&s.index)) {
+                let bar = &symbol.bars[signal.index];
+                let x = (signal.index - range.start) as f64 + offset;
+                // Con velas la marca va junto a la mecha; en las demás vistas, junto al cierre
+                let (y, color) = match signal.side {
+                    Side::Buy => (scale.domain.coordinate(if candles { bar.low } else { bar.close }) - row, Color::Green),
+                    Side::Sell => (scale.domain.coordinate(if candles { bar.high } else { bar.close }) + row, Color::Red),
+                };
+                let style = Style::default().fg(color).add_modifier(Modifier::BOLD);
+                ctx.print(x, y.clamp(low, high), Line::styled(signal.side.marker().to_string(), style));
+            }
+        });
+    f.render_widget(canvas, plot);
+}
+
+// El canvas descarta las líneas con algún extremo fuera de sus límites, así que se recortan
+// antes (Liang-Barsky) para que un dibujo que sale de la ventana se vea en la parte visible
+fn draw_segment(ctx: &mut Context, (x1, y1): (f64, f64), (x2, y2): (f64, f64), x_bounds: [f64; 2], y_bounds: [f64; 2], color: Color) {
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let (mut t0, mut t1) = (0.0f64, 1.0f64);
+    let edges = [(-dx, x1 - x_bounds[0]), (dx, x_bounds[1] - x1), (-dy, y1 - y_bounds[0]), (dy, y_bounds[1] - y1)];
+    for (p, q) in edges {
+        if p == 0.0 {
+            if q < 0.0 {
+                return;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    if t0 <= t1 {
+        ctx.draw(&CanvasLine { x1: x1 + t0 * dx, y1: y1 + t0 * dy, x2: x1 + t1 * dx, y2: y1 + t1 * dy, color });
+    }
+}
+
+// Leyenda de una línea en la esquina superior izquierda del trazado
+fn draw_legend<B: Backend>(f: &mut Frame<B>, entries: &[(String, Color)], plot: Rect) {
+    let legend: Vec<Span> = entries
+        .iter()
+        .flat_map(|(label, color)| {
+            let style = Style::default().fg(*color);
+            [Span::styled("━ ", style), Span::styled(format!("{} ", label), style)]
+        })
+        .collect();
+    if !legend.is_empty() {
+        let legend_area = Rect::new(plot.x, plot.y, plot.width, 1.min(plot.height));
+        f.render_widget(Paragraph::new(Line::from(legend)), legend_area);
+    }
+}
+
+// Lo que necesita un subpanel: barras visibles y valores de los indicadores en ellas
+struct PaneView<'a> {
+    pane: Pane,
+    bars: &'a [Candle],
+    values: &'a [IndicatorValues],
+    // Índice del cursor dentro de la ventana
+    cursor: Option<usize>,
+    // Con velas cada barra va centrada en su columna, como en `draw_candlestick_view`
+    candles: bool,
+    selected: bool,
+}
+
+fn draw_pane<B: Backend>(f: &mut Frame<B>, view: &PaneView, area: Rect, plot: Rect) {
+    let last = view.cursor.unwrap_or(view.values.len().saturating_sub(1));
+    let title = match (view.bars.get(last), view.values.get(last)) {
+        (Some(bar), Some(values)) => match view.pane {
+            Pane::Volume => format!("{} {:.0}", view.pane.as_str(), bar.volume),
+            Pane::Rsi => format!("{} {:.2}", view.pane.as_str(), values.rsi),
+            Pane::Macd => format!("{} {:.2} / Señal {:.2}", view.pane.as_str(), values.macd.0, values.macd.1),
+            Pane::Momentum => format!("{} {:.2}%", view.pane.as_str(), values.momentum),
+        },
+        _ => view.pane.as_str().to_string(),
+    };
+    let border = if view.selected { Color::Yellow } else { Color::White };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if inner.height == 0 || plot.width == 0 {
+        return;
+    }
+
+    let domain = view.pane.domain();
+    let guides = view.pane.guides();
+    let series = view.pane.lines(view.values);
+    let [y_min, y_max] = match view.pane {
+        // Las barras de volumen salen de cero
+        Pane::Volume => domain.bounds(view.bars.iter().map(|c| c.volume).chain([0.0])),
+        _ => domain.bounds(series.iter().flat_map(|(_, values)| values.iter().copied()).chain(guides.iter().copied())),
+    };
+
+    // Escala a la izquierda, en el hueco que deja el eje Y del precio
+    let labels_area = Rect::new(inner.x, inner.y, plot.x.saturating_sub(inner.x), inner.height);
+    if labels_area.width > 1 {
+        let mut labels = vec![Line::from(domain.format(y_max))];
+        labels.resize(inner.height as usize, Line::from(""));
+        if inner.height > 1 {
+            labels[inner.height as usize - 1] = Line::from(domain.format(y_min));
+        }
+        f.render_widget(Paragraph::new(labels).style(Style::default().fg(Color::Gray)), labels_area);
+    }
+
+    let offset = if view.candles { 0.5 } else { 0.0 };
+    let x_max = view.bars.len() as f64;
+    let canvas = Canvas::default()
+        .x_bounds([0.0, x_max])
+        .y_bounds([y_min, y_max])
+        .paint(|ctx| {
+            for guide in guides {
+                ctx.draw(&CanvasLine { x1: 0.0, y1: *guide, x2: x_max, y2: *guide, color: Color::DarkGray });
+            }
+            if view.pane == Pane::Volume {
+                for (i, bar) in view.bars.iter().enumerate() {
+                    let x = i as f64 + offset;
+                    let color = if bar.close >= bar.open { Color::Green } else { Color::Red };
+                    ctx.draw(&CanvasLine { x1: x, y1: 0.0, x2: x, y2: bar.volume, color });
+                }
+            }
+            for (line, (_, values)) in series.iter().enumerate() {
+                for (i, pair) in values.windows(2).enumerate() {
+                    let x = i as f64 + offset;
+                    ctx.draw(&CanvasLine { x1: x, y1: pair[0], x2: x + 1.0, y2: pair[1], color: line_color(line) });
+                }
+            }
+            if let Some(cursor) = view.cursor {
+                let x = cursor as f64 + offset;
+                ctx.draw(&CanvasLine { x1: x, y1: y_min, x2: x, y2: y_max, color: Color::White });
+            }
+        });
+    f.render_widget(canvas, Rect::new(plot.x, inner.y, plot.width, inner.height));
+}
+
+// Zona de trazado y barras visibles de la vista actual, para traducir posiciones del ratón
+pub fn plot_region(size: Rect, workspace: &Workspace, view: &ViewState) -> Option<(Rect, Range<usize>)> {
+    if !view.chart_type.shows_bars() {
+        return None;
+    }
+    let areas = chart_layout(size, workspace, view);
+    let symbol = workspace.current();
+    let range = view.viewport.range(&symbol.bars);
+    let scale = view_scale(view, symbol, range.clone());
+    let mut plot = plot_area(&view.chart_type, areas.chart, &symbol.chart_data[range.clone()], &scale);
+    if range.is_empty() || plot.area() == 0 {
+        return None;
+    }
+    // Los subpaneles comparten las columnas del precio, así que también sirven para apuntar
+    if let Some(last) = areas.panes.last() {
+        plot.height = last.bottom().saturating_sub(1).saturating_sub(plot.y);
+    }
+    Some((plot, range))
+}
+
+// Barra bajo una posición de la pantalla, para mover el cursor con el ratón
+pub fn bar_at(size: Rect, workspace: &Workspace, view: &ViewState, column: u16, row: u16) -> Option<usize> {
+    let (plot, range) = plot_region(size, workspace, view)?;
+    if !plot.intersects(Rect::new(column, row, 1, 1)) {
+        return None;
+    }
+
+    let x = (column - plot.x) as f64 / plot.width.saturating_sub(1).max(1) as f64 * range.len() as f64;
+    let offset = if view.chart_type == ChartType::Candlestick { x.floor() } else { x.round() };
+    Some(range.start + (offset as usize).min(range.len() - 1))
+}
+
+// Barra y precio bajo una posición de la pantalla, para colocar dibujos con el ratón. Sólo en
+// las vistas con el eje de precio y dentro de su trazado, no en los subpaneles.
+pub fn anchor_at(size: Rect, workspace: &Workspace, view: &ViewState, column: u16, row: u16) -> Option<Anchor> {
+    if !view.chart_type.shows_bars() || view.chart_type.indicator_pane().is_some() {
+        return None;
+    }
+    let areas = chart_layout(size, workspace, view);
+    let symbol = workspace.current();
+    let range = view.viewport.range(&symbol.bars);
+    let scale = view_scale(view, symbol, range.clone());
+    let plot = plot_area(&view.chart_type, areas.chart, &symbol.chart_data[range], &scale);
+    if !plot.intersects(Rect::new(column, row, 1, 1)) {
+        return None;
+    }
+
+    let index = bar_at(size, workspace, view, column, row)?;
+    let [low, high] = scale.bounds;
+    let fraction = ((row - plot.y) as f64 + 0.5) / plot.height as f64;
+    let precio = scale.domain.value_at(high - fraction * (high - low));
+    Some(Anchor { fecha: symbol.bars[index].date.clone(), precio: (precio * 100.0).round() / 100.0 })
+}
+
+// Si la posición cae sobre la lista de señales
+pub fn over_signals_panel(size: Rect, workspace: &Workspace, view: &ViewState, column: u16, row: u16) -> bool {
+    chart_layout(size, workspace, view)
+        .signals
+        .is_some_and(|area| area.intersects(Rect::new(column, row, 1, 1)))
+}
+
+// Vista cuya pestaña del panel de controles está bajo una posición de la pantalla
+pub fn chart_type_at(size: Rect, workspace: &Workspace, view: &ViewState, column: u16, row: u16) -> Option<ChartType> {
+    let areas = chart_layout(size, workspace, view);
+    let inner = Block::default().borders(Borders::ALL).inner(areas.controls);
+    // Las pestañas ocupan la segunda línea del panel
+    if row != inner.y + 1 || column < inner.x {
+        return None;
+    }
+    let column = column - inner.x;
+    view_tabs()
+        .into_iter()
+        .find(|(_, start, label)| (*start..start + label.chars().count() as u16).contains(&column))
+        .map(|(chart_type, _, _)| chart_type)
+}
+
+// Pestañas de las vistas: tipo, columna en la que empieza y texto
+fn view_tabs() -> Vec<(ChartType, u16, String)> {
+    let mut column = VIEW_TABS_PREFIX.chars().count() as u16;
+    ChartType::all()
+        .into_iter()
+        .map(|chart_type| {
+            let label = format!(" {} ", chart_type.as_str());
+            let start = column;
+            column += label.chars().count() as u16 + 1;
+            (chart_type, start, label)
+        })
+        .collect()
+}
+
+// Lectura de la barra seleccionada: fecha, OHLCV, variación y los indicadores en ese punto
+fn create_bar_panel(symbol: &Symbol, index: usize) -> Paragraph<'static> {
+    let bar = &symbol.bars[index];
+    let values = symbol.indicator_history()[index];
+    let change = index
+        .checked_sub(1)
+        .map(|previous| (bar.close / symbol.bars[previous].close - 1.0) * 100.0);
+
+    let row = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{}: ", label), Style::default().fg(Color::Yellow)),
+            Span::styled(value, Style::default().fg(Color::White)),
+        ])
+    };
+    let change_color = match change {
+        Some(change) if change < 0.0 => Color::Red,
+        Some(_) => Color::Green,
+        None => Color::Gray,
+    };
+
+    let info_text = vec![
+        Line::from(vec![
+            Span::styled(format!("Barra {}", bar.date), Style::default().fg(Color::Green)),
+            Span::styled(if bar.gap { " (hueco)" } else { "" }, Style::default().fg(Color::DarkGray)),
+        ]),
+        row("Apertura", format!("${:.2}", bar.open)),
+        row("Máximo", format!("${:.2}", bar.high)),
+        row("Mínimo", format!("${:.2}", bar.low)),
+        row("Cierre", format!("${:.2}", bar.close)),
+        row("Volumen", format!("{:.0}", bar.volume)),
+        Line::from(vec![
+            Span::styled("% var.: ", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                change.map_or("-".to_string(), |change| format!("{:+.2}%", change)),
+                Style::default().fg(change_color),
+            ),
+        ]),
+        row("RSI (14)", format!("{:.2}", values.rsi)),
+        row("MACD", format!("{:.2} / Señal: {:.2}", values.macd.0, values.macd.1)),
+        row("Bollinger Bands - Superior", format!("{:.2}", values.bollinger.0)),
+        row("Bollinger Bands - Medio", format!("{:.2}", values.bollinger.1)),
+        row("Bollinger Bands - Inferior", format!("{:.2}", values.bollinger.2)),
+        row("Momentum (14)", format!("{:.2}%", values.momentum)),
+        row("SMA (20)", format!("${:.2}", values.sma)),
+    ];
+
+    Paragraph::new(info_text)
+        .block(Block::default().title("Estadísticas").borders(Borders::ALL))
+        .alignment(Alignment::Left)
+}
+
+fn create_symbol_list(workspace: &Workspace) -> Paragraph<'static> {
+    let lines: Vec<Line> = workspace
+        .symbols
+        .iter()
+        .enumerate()
+        .map(|(i, symbol)| {
+            let last = symbol.bars.last().map(|c| c.close).unwrap_or(0.0);
+            let style = if i == workspace.selected {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let mut spans = vec![
+                Span::styled(format!("{:<12}", symbol.name), style),
+                Span::styled(format!(" ${:.2}", last), Style::default().fg(Color::Gray)),
+            ];
+            // Aviso de problemas de calidad detectados al cargar
+            if !symbol.report.is_clean() {
+                spans.push(Span::styled(
+                    format!(" !{}", symbol.report.issues.len()),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    Paragraph::new(lines)
+        .block(Block::default().title("Símbolos").borders(Borders::ALL))
+        .alignment(Alignment::Left)
+}
+
+// Señales del símbolo de la más reciente a la más antigua, empezando en `scroll`. Las de la
+// ventana visible van resaltadas.
+fn create_signals_panel(symbol: &Symbol, scroll: usize, visible: &Range<usize>) -> Paragraph<'static> {
+    let signals = symbol.signals();
+    let lines: Vec<Line> = signals
+        .iter()
+        .rev()
+        .skip(scroll)
+        .map(|signal: &Signal| {
+            let color = match signal.side {
+                Side::Buy => Color::Green,
+                Side::Sell => Color::Red,
+            };
+            let mut style = Style::default().fg(color);
+            if visible.contains(&signal.index) {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            Line::from(vec![
+                Span::styled(format!("{} {} ", signal.side.marker(), symbol.bars[signal.index].date), style),
+                Span::styled(format!("{:<6} ", signal.side.as_str()), style),
+                Span::styled(signal.rule.as_str().to_string(), Style::default().fg(Color::Gray)),
+            ])
+        })
+        .collect();
+
+    let title = if signals.is_empty() {
+        "Señales (ninguna)".to_string()
+    } else {
+        format!("Señales ({}) {}/{}", signals.len(), scroll.min(signals.len() - 1) + 1, signals.len())
+    };
+    Paragraph::new(lines).block(Block::default().title(title).borders(Borders::ALL))
+}
+
+// Última alerta disparada, cuántas más hay pendientes y cómo descartarlas
+fn create_notification_bar(notifications: &[String]) -> Paragraph<'static> {
+    let style = Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD);
+    let mut text = vec![Span::styled(format!(" 🔔 {} ", notifications.last().map_or("", String::as_str)), style)];
+    if notifications.len() > 1 {
+        text.push(Span::styled(format!("(+{} anteriores) ", notifications.len() - 1), style));
+    }
+    text.push(Span::styled("Z descartar ", style.fg(Color::Yellow)));
+    Paragraph::new(Line::from(text)).style(style)
+}
+
+const VIEW_TABS_PREFIX: &str = "Vistas: ";
+
+// `window` son las fechas visibles cuando hay zoom
+fn create_control_panel(view: &ViewState, multi_symbol: bool, window: Option<(Fecha, Fecha)>) -> Paragraph<'static> {
+    let current_type = &view.chart_type;
+    let mut text = Vec::new();
+    // Con una herramienta de dibujo activa lo primero es qué hacer con ella
+    if let Some(sketch) = &view.sketch {
+        let prompt = match &sketch.text {
+            Some(typed) => format!("✎ Nota: {}▏ Intro guardar · Esc cancelar ", typed),
+            None => format!(
+                "✎ {}: punto {}/{} (clic o Intro en el cursor) · Esc salir ",
+                sketch.tool.as_str(),
+                sketch.points.len() + 1,
+                sketch.tool.points(),
+            ),
+        };
+        text.push(Span::styled(prompt, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw("| "));
+    }
+    // El estado va delante de los controles: la línea no cabe entera y se corta por la derecha
+    if let Some(status) = &view.status {
+        text.push(Span::styled(status.clone(), Style::default().fg(Color::Cyan)));
+        text.push(Span::raw(" | "));
+    }
+    text.extend([
         Span::styled("Controles: ", Style::default().fg(Color::White)),
         Span::styled("Q", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
         Span::raw(" Salir | "),
         Span::styled("T", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-        Span::raw(format!(" Cambiar vista (actual: {}) ", current_type.as_str())),
-    ];
+        Span::raw(format!(" Cambiar vista (actual: {}) | ", current_type.as_str())),
+        Span::styled("R", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        Span::raw(format!(" Temporalidad (actual: {}) ", view.timeframe.as_str())),
+    ]);
+    if *current_type == ChartType::Comparison {
+        text.push(Span::raw("| "));
+        text.push(Span::styled("N", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(format!(" {} ", view.comparison.normalization.as_str())));
+        text.push(Span::styled("[ ]", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(" Fecha base "));
+    }
+    if *current_type == ChartType::Correlation {
+        text.push(Span::raw("| "));
+        text.push(Span::styled("M", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(format!(" {} ", view.correlation.method.as_str())));
+        text.push(Span::styled("B", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(" Referencia "));
+        text.push(Span::styled("E", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(" Exportar CSV "));
+    }
+    if multi_symbol {
+        text.push(Span::raw("| "));
+        text.push(Span::styled("Tab", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(" Símbolo "));
+    }
+    text.push(Span::raw("| "));
+    text.push(Span::styled("+ -", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+    text.push(Span::raw(" Zoom "));
+    text.push(Span::styled("← →", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+    text.push(Span::raw(if view.cursor.is_some() { " Mover cursor " } else { " Desplazar " }));
+    text.push(Span::styled("C", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+    text.push(Span::raw(" Cursor "));
+    if current_type.is_price_view() {
+        text.push(Span::styled("1-8", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(" Superposiciones "));
+        text.push(Span::styled("L", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(if view.log_scale { " Escala lineal " } else { " Escala log " }));
+        text.push(Span::styled("G", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(" Señales "));
+        text.push(Span::styled("D", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(" Dibujar "));
+        if view.sketch.is_some() {
+            text.push(Span::styled("Retroceso", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+            text.push(Span::raw(" Borrar dibujo "));
+        }
+        text.push(Span::styled("A X", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(" Añadir/quitar panel "));
+        text.push(Span::styled("P ↑ ↓", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(" Elegir/mover panel "));
+    }
+    if let Some((first, last)) = window {
+        text.push(Span::raw(format!("| {} - {} ", first, last)));
+        text.push(Span::styled("Inicio Fin 0", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        text.push(Span::raw(" Saltar / Restablecer "));
+    }
+    match &view.replay {
+        Some(replay) => {
+            text.push(Span::raw("| "));
+            text.push(Span::styled(
+                format!("Replay {} {} ", replay.cursor, if replay.playing { "▶" } else { "⏸" }),
+                Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+            ));
+            text.push(Span::styled(", .", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+            text.push(Span::raw(" Paso "));
+            text.push(Span::styled("Espacio", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+            text.push(Span::raw(" Reproducir "));
+            text.push(Span::styled("< >", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+            text.push(Span::raw(format!(" {} barras/s ", replay.speed())));
+            text.push(Span::styled("V", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+            text.push(Span::raw(" Salir del replay "));
+        }
+        None => {
+            text.push(Span::raw("| "));
+            text.push(Span::styled("V", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+            text.push(Span::raw(" Replay "));
+        }
+    }
+
+    // Segunda línea: una pestaña por vista, que también se puede pulsar con el ratón
+    let mut tabs = vec![Span::styled(VIEW_TABS_PREFIX, Style::default().fg(Color::White))];
+    for (chart_type, _, label) in view_tabs() {
+        let style = if chart_type == *current_type {
+            Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        tabs.push(Span::styled(label, style));
+        tabs.push(Span::styled("│", Style::default().fg(Color::DarkGray)));
+    }
+    tabs.pop();
 
-    Paragraph::new(Line::from(text))
+    Paragraph::new(vec![Line::from(text), Line::from(tabs).alignment(Alignment::Left)])
         .block(Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Yellow)))
@@ -224,19 +1393,17 @@ fn create_control_panel(current_type: &ChartType) -> Paragraph<'static> {
 
 fn draw_candlestick_view<B: Backend>(
     f: &mut Frame<B>,
-    candles: &Vec<Candle>,
+    candles: &[Candle],
+    scale: &ViewScale,
+    plot: Rect,
     area: Rect,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let max_price = candles.iter().map(|c| c.high).fold(f64::NEG_INFINITY, f64::max);
-    let min_price = candles.iter().map(|c| c.low).fold(f64::INFINITY, f64::min);
-    let price_range = max_price - min_price;
-    let y_min = min_price - (price_range * 0.05);
-    let y_max = max_price + (price_range * 0.05);
+    let axis = |value: f64| scale.domain.coordinate(value);
+    let chart_area = draw_view_frame(f, "Gráfico de Velas", scale, plot, area);
 
     let canvas = Canvas::default()
-        .block(Block::default().title("Gráfico de Velas").borders(Borders::ALL))
         .x_bounds([0.0, candles.len() as f64])
-        .y_bounds([y_min, y_max])
+        .y_bounds(scale.bounds)
         .paint(|ctx| {
             for (i, candle) in candles.iter().enumerate() {
                 let x = i as f64;
@@ -245,15 +1412,15 @@ fn draw_candlestick_view<B: Backend>(
                 // Dibujar la mecha
                 ctx.draw(&CanvasLine {
                     x1: x + 0.5,
-                    y1: candle.low,
+                    y1: axis(candle.low),
                     x2: x + 0.5,
-                    y2: candle.high,
+                    y2: axis(candle.high),
                     color,
                 });
 
                 // Dibujar el cuerpo
-                let body_top = f64::max(candle.open, candle.close);
-                let body_bottom = f64::min(candle.open, candle.close);
+                let body_top = f64::max(axis(candle.open), axis(candle.close));
+                let body_bottom = f64::min(axis(candle.open), axis(candle.close));
                 ctx.draw(&CanvasRectangle {
                     x: x + 0.2,
                     y: body_bottom,
@@ -264,54 +1431,112 @@ fn draw_candlestick_view<B: Backend>(
             }
         });
 
-    f.render_widget(canvas, area);
+    f.render_widget(canvas, chart_area);
     Ok(())
 }
 
+fn candle_y_bounds(candles: &[Candle]) -> [f64; 2] {
+    let max_price = candles.iter().map(|c| c.high).fold(f64::NEG_INFINITY, f64::max);
+    let min_price = candles.iter().map(|c| c.low).fold(f64::INFINITY, f64::min);
+    let price_range = max_price - min_price;
+    [min_price - (price_range * 0.05), max_price + (price_range * 0.05)]
+}
+
 fn get_candle_color(candle: &Candle) -> Color {
-    if candle.close > candle.open {
+    if candle.gap {
+        Color::DarkGray
+    } else if candle.close > candle.open {
         Color::Green
     } else {
         Color::Red
     }
 }
 
-fn convert_to_candles(data: &[(String, f64)], period: usize) -> Vec<Candle> {
-    let mut candles = Vec::new();
-    for chunk in data.chunks(period) {
-        if !chunk.is_empty() {
-            let open = chunk[0].1;
-            let close = chunk[chunk.len() - 1].1;
-            let high = chunk.iter().map(|(_, price)| *price).fold(f64::NEG_INFINITY, f64::max);
-            let low = chunk.iter().map(|(_, price)| *price).fold(f64::INFINITY, f64::min);
-
-            candles.push(Candle {
-                date: chunk[0].0.clone(),
-                open,
-                high,
-                low,
-                close,
-            });
+// Perfil de volumen junto al gráfico de precio. Sus niveles reparten los mismos límites que el
+// eje vertical del gráfico, uno por fila del trazado, para que cada uno quede a la altura de su
+// precio; en escala logarítmica son iguales en log10 y no en dólares.
+fn draw_volume_profile<B: Backend>(
+    f: &mut Frame<B>,
+    symbol: &Symbol,
+    range: Range<usize>,
+    scale: &ViewScale,
+    plot: Rect,
+    area: Rect,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    let rows = Rect::new(inner.x, plot.y, inner.width, plot.height).intersection(inner);
+    let num_bins = rows.height.max(1) as usize;
+    let domain = scale.domain;
+    let [low, high] = scale.bounds;
+    let edges = (0..=num_bins)
+        .map(|i| domain.value_at(low + (high - low) * i as f64 / num_bins as f64))
+        .collect();
+    let profile = match symbol.volume_profile(range, edges, 70) {
+        Some(profile) => profile,
+        None => {
+            let empty = Paragraph::new("Sin datos de volumen")
+                .block(Block::default().title("Perfil de Volumen").borders(Borders::ALL));
+            f.render_widget(empty, area);
+            return Ok(());
         }
+    };
+
+    let max_volume = profile.bins.iter().map(|(_, _, v)| *v).fold(0.0, f64::max);
+    let title = format!("Vol. POC ${:.0}", profile.poc_price());
+    f.render_widget(Block::default().title(title).borders(Borders::ALL), area);
+
+    let canvas = Canvas::default()
+        .marker(symbols::Marker::Block)
+        .x_bounds([0.0, max_volume.max(1.0)])
+        .y_bounds(scale.bounds)
+        .paint(|ctx| {
+            for (i, (low, high, volume)) in profile.bins.iter().enumerate() {
+                let y = (domain.coordinate(*low) + domain.coordinate(*high)) / 2.0;
+                ctx.draw(&CanvasLine {
+                    x1: 0.0,
+                    y1: y,
+                    x2: *volume,
+                    y2: y,
+                    color: get_profile_color(&profile, i),
+                });
+            }
+            ctx.layer();
+            ctx.print(0.0, domain.coordinate(profile.value_area_high), Line::styled(
+                format!("VAH {:.0}", profile.value_area_high),
+                Style::default().fg(Color::White),
+            ));
+            ctx.print(0.0, domain.coordinate(profile.value_area_low), Line::styled(
+                format!("VAL {:.0}", profile.value_area_low),
+                Style::default().fg(Color::White),
+            ));
+        });
+
+    f.render_widget(canvas, rows);
+    Ok(())
+}
+
+fn get_profile_color(profile: &VolumeProfile, bin: usize) -> Color {
+    let (low, high, _) = profile.bins[bin];
+    if bin == profile.poc {
+        Color::Yellow
+    } else if low >= profile.value_area_low && high <= profile.value_area_high {
+        Color::Cyan
+    } else {
+        Color::DarkGray
     }
-    candles
 }
 
+// Eje de tiempo de la ventana visible: etiquetas repartidas entre su primera y su última fecha
 fn create_x_axis(data: &[(String, f64)]) -> Axis<'static> {
     let data_len = data.len() as f64;
-    let num_labels = 6;
-    let step = (data_len / (num_labels - 1) as f64).floor() as usize;
-
-    let mut labels = Vec::new();
-    for i in 0..num_labels {
-        let idx = (i * step).min(data.len() - 1);
-        if idx < data.len() {
-            labels.push(Span::styled(
-                data[idx].0.clone(),
-                Style::default().fg(Color::Gray)
-            ));
-        }
-    }
+    let num_labels = 6.min(data.len());
+
+    let labels: Vec<Span> = (0..num_labels)
+        .map(|i| {
+            let idx = i * (data.len() - 1) / (num_labels - 1).max(1);
+            Span::styled(data[idx].0.clone(), Style::default().fg(Color::Gray))
+        })
+        .collect();
 
     Axis::default()
         .title("Tiempo")
@@ -320,30 +1545,16 @@ fn create_x_axis(data: &[(String, f64)]) -> Axis<'static> {
         .labels(labels)
 }
 
-fn create_y_axis(data: &[(String, f64)]) -> Axis<'static> {
-    let max_value = data.iter().map(|(_, v)| v).fold(f64::NEG_INFINITY, |a, &b| a.max(b));
-    let min_value = data.iter().map(|(_, v)| v).fold(f64::INFINITY, |a, &b| a.min(b));
-    let range = max_value - min_value;
-    let bounds = [
-        (min_value - range * 0.05).max(0.0),
-        max_value + range * 0.05
-    ];
-
-    let num_labels = 5;
-    let step = (bounds[1] - bounds[0]) / (num_labels - 1) as f64;
-
-    let labels: Vec<Span> = (0..num_labels)
-        .map(|i| {
-            let value = bounds[0] + step * i as f64;
-            Span::styled(
-                format!("${:.0}", value),
-                Style::default().fg(Color::Gray)
-            )
-        })
+// Eje vertical de un trazado de `rows` filas
+fn value_axis(title: &str, domain: AxisDomain, bounds: [f64; 2], rows: u16) -> Axis<'static> {
+    let labels: Vec<Span> = domain
+        .labels(bounds, rows)
+        .into_iter()
+        .map(|label| Span::styled(label, Style::default().fg(Color::Gray)))
         .collect();
 
     Axis::default()
-        .title("Precio")
+        .title(title.to_string())
         .style(Style::default().fg(Color::Gray))
         .bounds(bounds)
         .labels(labels)
@@ -351,12 +1562,15 @@ fn create_y_axis(data: &[(String, f64)]) -> Axis<'static> {
 
 fn draw_line_view<B: Backend>(
     f: &mut Frame<B>,
+    title: &str,
     data: &[(String, f64)],
+    scale: &ViewScale,
+    plot: Rect,
     area: Rect,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let line_data: Vec<(f64, f64)> = data.iter()
         .enumerate()
-        .map(|(i, (_, price))| (i as f64, *price))
+        .map(|(i, (_, price))| (i as f64, scale.domain.coordinate(*price)))
         .collect();
 
     let datasets = vec![Dataset::default()
@@ -366,23 +1580,27 @@ fn draw_line_view<B: Backend>(
         .data(&line_data)
     ];
 
+    let chart_area = draw_view_frame(f, title, scale, plot, area);
     let chart = Chart::new(datasets)
-        .block(Block::default().title("Gráfico de Línea").borders(Borders::ALL))
         .x_axis(create_x_axis(data))
-        .y_axis(create_y_axis(data));
+        .y_axis(value_axis("Precio", scale.domain, scale.bounds, plot.height))
+        // La leyenda la dibuja `draw_legend`, junto a la de las superposiciones
+        .hidden_legend_constraints((Constraint::Length(0), Constraint::Length(0)));
 
-    f.render_widget(chart, area);
+    f.render_widget(chart, chart_area);
     Ok(())
 }
 
 fn draw_dots_view<B: Backend>(
     f: &mut Frame<B>,
     data: &[(String, f64)],
+    scale: &ViewScale,
+    plot: Rect,
     area: Rect,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let dot_data: Vec<(f64, f64)> = data.iter()
         .enumerate()
-        .map(|(i, (_, price))| (i as f64, *price))
+        .map(|(i, (_, price))| (i as f64, scale.domain.coordinate(*price)))
         .collect();
 
     let datasets = vec![Dataset::default()
@@ -392,23 +1610,27 @@ fn draw_dots_view<B: Backend>(
         .data(&dot_data)
     ];
 
+    let chart_area = draw_view_frame(f, "Gráfico de Puntos", scale, plot, area);
     let chart = Chart::new(datasets)
-        .block(Block::default().title("Gráfico de Puntos").borders(Borders::ALL))
         .x_axis(create_x_axis(data))
-        .y_axis(create_y_axis(data));
+        .y_axis(value_axis("Precio", scale.domain, scale.bounds, plot.height))
+        // La leyenda la dibuja `draw_legend`, junto a la de las superposiciones
+        .hidden_legend_constraints((Constraint::Length(0), Constraint::Length(0)));
 
-    f.render_widget(chart, area);
+    f.render_widget(chart, chart_area);
     Ok(())
 }
 
 fn draw_bars_view<B: Backend>(
     f: &mut Frame<B>,
     data: &[(String, f64)],
+    scale: &ViewScale,
+    plot: Rect,
     area: Rect,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let bar_data: Vec<(f64, f64)> = data.iter()
         .enumerate()
-        .map(|(i, (_, price))| (i as f64, *price))
+        .map(|(i, (_, price))| (i as f64, scale.domain.coordinate(*price)))
         .collect();
 
     let datasets = vec![Dataset::default()
@@ -418,180 +1640,407 @@ fn draw_bars_view<B: Backend>(
         .data(&bar_data)
     ];
 
+    let chart_area = draw_view_frame(f, "Gráfico de Barras", scale, plot, area);
     let chart = Chart::new(datasets)
-        .block(Block::default().title("Gráfico de Barras").borders(Borders::ALL))
         .x_axis(create_x_axis(data))
-        .y_axis(create_y_axis(data));
+        .y_axis(value_axis("Precio", scale.domain, scale.bounds, plot.height))
+        // La leyenda la dibuja `draw_legend`, junto a la de las superposiciones
+        .hidden_legend_constraints((Constraint::Length(0), Constraint::Length(0)));
 
-    f.render_widget(chart, area);
+    f.render_widget(chart, chart_area);
     Ok(())
 }
 
-fn draw_macd_view<B: Backend>(
+// Indicador en su propio eje a la izquierda y el precio, atenuado, en un eje secundario a la derecha
+fn draw_indicator_view<B: Backend>(
     f: &mut Frame<B>,
+    title: &str,
+    scale: &ViewScale,
     data: &[(String, f64)],
+    plot: Rect,
     area: Rect,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let data_vec: Vec<(String, f64)> = data.to_vec();
-    let (macd_line, signal_line) = calculate_macd(&data_vec);
-    let macd_data: Vec<(f64, f64)> = data.iter()
-        .enumerate()
-        .map(|(i, _)| (i as f64, macd_line))
+    let chart_area = draw_view_frame(f, title, scale, plot, area);
+    let secondary = scale.secondary.map_or(scale.bounds, |(_, bounds)| bounds);
+
+    let x_max = data.len() as f64;
+    let guide_data: Vec<Vec<(f64, f64)>> = scale.guides.iter().map(|g| vec![(0.0, *g), (x_max, *g)]).collect();
+    let line_data: Vec<Vec<(f64, f64)>> = scale
+        .lines
+        .iter()
+        .map(|(_, values)| values.iter().enumerate().map(|(i, v)| (i as f64, *v)).collect())
         .collect();
-    let signal_data: Vec<(f64, f64)> = data.iter()
-        .enumerate()
-        .map(|(i, _)| (i as f64, signal_line))
+    let mut datasets: Vec<Dataset> = guide_data
+        .iter()
+        .map(|guide| {
+            Dataset::default()
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(Color::DarkGray))
+                .data(guide)
+        })
         .collect();
-
-    let datasets = vec![
+    datasets.extend(line_data.iter().enumerate().map(|(i, line)| {
         Dataset::default()
-            .name("MACD")
             .marker(symbols::Marker::Braille)
-            .style(Style::default().fg(Color::Cyan))
-            .data(&macd_data),
-        Dataset::default()
-            .name("Signal")
-            .marker(symbols::Marker::Dot)
-            .style(Style::default().fg(Color::Red))
-            .data(&signal_data),
-    ];
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(line_color(i)))
+            .data(line)
+    }));
+
+    // El precio comparte las columnas del indicador pero usa la escala del eje derecho;
+    // se dibuja antes para que el indicador quede por encima
+    let price: Vec<(f64, f64)> = data.iter().enumerate().map(|(i, (_, v))| (i as f64, *v)).collect();
+    let canvas = Canvas::default()
+        .x_bounds([0.0, x_max])
+        .y_bounds(secondary)
+        .paint(|ctx| {
+            for pair in price.windows(2) {
+                let (x1, y1) = pair[0];
+                let (x2, y2) = pair[1];
+                ctx.draw(&CanvasLine { x1, y1, x2, y2, color: Color::DarkGray });
+            }
+        });
+    f.render_widget(canvas, plot);
 
+    let y_title = scale.lines.first().map_or("", |(name, _)| name);
     let chart = Chart::new(datasets)
-        .block(Block::default().title("Gráfico de MACD").borders(Borders::ALL))
         .x_axis(create_x_axis(data))
-        .y_axis(create_y_axis(data));
-
-    f.render_widget(chart, area);
+        .y_axis(value_axis(y_title, scale.domain, scale.bounds, plot.height));
+    f.render_widget(chart, chart_area);
     Ok(())
 }
 
-fn draw_sma_view<B: Backend>(
-    f: &mut Frame<B>,
-    data: &[(String, f64)],
-    area: Rect,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let data_vec: Vec<(String, f64)> = data.to_vec();
-    let sma_value = calculate_sma(&data_vec, 20);
-    let sma_data: Vec<(f64, f64)> = data.iter()
-        .enumerate()
-        .map(|(i, _)| (i as f64, sma_value))
-        .collect();
-
-    let datasets = vec![Dataset::default()
-        .name("SMA")
-        .marker(symbols::Marker::Braille)
-        .style(Style::default().fg(Color::Cyan))
-        .data(&sma_data)
-    ];
-
-    let chart = Chart::new(datasets)
-        .block(Block::default().title("Gráfico de SMA").borders(Borders::ALL))
-        .x_axis(create_x_axis(data))
-        .y_axis(create_y_axis(data));
-
-    f.render_widget(chart, area);
-    Ok(())
+// Marco de una vista con las etiquetas del eje derecho, si lo tiene. Devuelve la zona que
+// queda para el gráfico.
+fn draw_view_frame<B: Backend>(f: &mut Frame<B>, title: &str, scale: &ViewScale, plot: Rect, area: Rect) -> Rect {
+    let block = Block::default().title(title.to_string()).borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let Some((domain, bounds)) = scale.secondary else {
+        return inner;
+    };
+    let (chart_area, axis_area) = secondary_axis_split(inner, domain, bounds);
+
+    // Etiquetas a la altura de las del eje izquierdo, como las coloca `Chart`
+    if plot.height > 1 {
+        let mut labels = vec![Line::from(""); axis_area.height as usize];
+        for (i, label) in domain.labels(bounds, plot.height).into_iter().enumerate() {
+            let row = (plot.height - 1) as usize - i * (plot.height - 1) as usize / (LABELS - 1);
+            if let Some(line) = labels.get_mut(row) {
+                *line = Line::from(format!(" {}", label));
+            }
+        }
+        f.render_widget(Paragraph::new(labels).style(Style::default().fg(Color::DarkGray)), axis_area);
+    }
+    chart_area
 }
 
-fn draw_rsi_view<B: Backend>(
+const SERIES_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Green,
+    Color::Red,
+    Color::Blue,
+];
+
+fn draw_comparison_view<B: Backend>(
     f: &mut Frame<B>,
-    data: &[(String, f64)],
+    workspace: &Workspace,
+    settings: &ComparisonSettings,
     area: Rect,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let data_vec: Vec<(String, f64)> = data.to_vec();
-    let rsi_value = calculate_rsi(&data_vec, 14);
-    let rsi_data: Vec<(f64, f64)> = data.iter()
+    let comparison = workspace.comparison(settings);
+    let comparison = match comparison.as_ref() {
+        Some(comparison) => comparison,
+        None => {
+            let empty = Paragraph::new("Las series no tienen fechas en común")
+                .block(Block::default().title("Comparación").borders(Borders::ALL));
+            f.render_widget(empty, area);
+            return Ok(());
+        }
+    };
+
+    let datasets: Vec<Dataset> = comparison
+        .series
+        .iter()
         .enumerate()
-        .map(|(i, _)| (i as f64, rsi_value))
+        .map(|(i, (name, points))| {
+            Dataset::default()
+                .name(name.clone())
+                .marker(symbols::Marker::Braille)
+                .style(Style::default().fg(SERIES_COLORS[i % SERIES_COLORS.len()]))
+                .data(points)
+        })
         .collect();
 
-    let datasets = vec![Dataset::default()
-        .name("RSI")
-        .marker(symbols::Marker::Braille)
-        .style(Style::default().fg(Color::Cyan))
-        .data(&rsi_data)
-    ];
+    let values = comparison.series.iter().flat_map(|(_, points)| points.iter().map(|(_, v)| *v));
+    let max_value = values.clone().fold(f64::NEG_INFINITY, f64::max);
+    let min_value = values.fold(f64::INFINITY, f64::min);
+    let range = (max_value - min_value).max(1.0);
+    let bounds = [min_value - range * 0.05, max_value + range * 0.05];
+    let step = (bounds[1] - bounds[0]) / 4.0;
+    let y_labels: Vec<Span> = (0..5)
+        .map(|i| {
+            let value = bounds[0] + step * i as f64;
+            let label = match settings.normalization {
+                Normalization::Base100 => format!("{:.0}", value),
+                Normalization::Percent => format!("{:+.0}%", value),
+            };
+            Span::styled(label, Style::default().fg(Color::Gray))
+        })
+        .collect();
+
+    let dates: Vec<(String, f64)> = comparison.dates.iter().map(|d| (d.to_string(), 0.0)).collect();
+    let title = format!(
+        "Comparación ({}, base {})",
+        settings.normalization.as_str(),
+        comparison.base
+    );
 
     let chart = Chart::new(datasets)
-        .block(Block::default().title("Gráfico de RSI").borders(Borders::ALL))
-        .x_axis(create_x_axis(data))
-        .y_axis(create_y_axis(data));
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .x_axis(create_x_axis(&dates))
+        .y_axis(
+            Axis::default()
+                .title(settings.normalization.as_str().to_string())
+                .style(Style::default().fg(Color::Gray))
+                .bounds(bounds)
+                .labels(y_labels),
+        );
 
     f.render_widget(chart, area);
     Ok(())
 }
 
-fn draw_bollinger_bands_view<B: Backend>(
+// Curva de capital del backtest frente a comprar en la primera apertura y mantener
+fn draw_backtest_view<B: Backend>(
     f: &mut Frame<B>,
-    data: &[(String, f64)],
+    symbol: &Symbol,
+    result: &BacktestResult,
     area: Rect,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let data_vec: Vec<(String, f64)> = data.to_vec();
-    let (upper, middle, lower) = calculate_bollinger_bands(&data_vec, 20);
-    let upper_data: Vec<(f64, f64)> = data.iter()
-        .enumerate()
-        .map(|(i, _)| (i as f64, upper))
-        .collect();
-    let middle_data: Vec<(f64, f64)> = data.iter()
+    let Some(first) = symbol.bars.first() else {
+        let empty = Paragraph::new("Sin barras para el backtest")
+            .block(Block::default().title("Backtest").borders(Borders::ALL));
+        f.render_widget(empty, area);
+        return Ok(());
+    };
+
+    let equity: Vec<(f64, f64)> = result.equity.iter().enumerate().map(|(i, v)| (i as f64, *v)).collect();
+    let hold: Vec<(f64, f64)> = symbol
+        .bars
+        .iter()
         .enumerate()
-        .map(|(i, _)| (i as f64, middle))
+        .map(|(i, bar)| (i as f64, result.capital * bar.close / first.open))
         .collect();
-    let lower_data: Vec<(f64, f64)> = data.iter()
-        .enumerate()
-        .map(|(i, _)| (i as f64, lower))
-        .collect();
-
     let datasets = vec![
         Dataset::default()
-            .name("Upper Band")
+            .name("Estrategia")
             .marker(symbols::Marker::Braille)
             .style(Style::default().fg(Color::Cyan))
-            .data(&upper_data),
+            .data(&equity),
         Dataset::default()
-            .name("Middle Band")
-            .marker(symbols::Marker::Dot)
-            .style(Style::default().fg(Color::Red))
-            .data(&middle_data),
-        Dataset::default()
-            .name("Lower Band")
-            .marker(symbols::Marker::Dot)
-            .style(Style::default().fg(Color::Green))
-            .data(&lower_data),
+            .name("Comprar y mantener")
+            .marker(symbols::Marker::Braille)
+            .style(Style::default().fg(Color::DarkGray))
+            .data(&hold),
     ];
 
+    let values = equity.iter().chain(&hold).map(|(_, v)| *v);
+    let max_value = values.clone().fold(f64::NEG_INFINITY, f64::max);
+    let min_value = values.fold(f64::INFINITY, f64::min);
+    let range = (max_value - min_value).max(1.0);
+    // El capital no baja de cero: sin apalancamiento no se puede perder más de lo invertido
+    let bounds = [(min_value - range * 0.05).max(0.0), max_value + range * 0.05];
+    let rows = Block::default().borders(Borders::ALL).inner(area).height.saturating_sub(2);
+
+    let dates: Vec<(String, f64)> = symbol.bars.iter().map(|c| (c.date.clone(), 0.0)).collect();
+    let title = format!(
+        "Backtest: {} ({:+.2}%)",
+        result.strategy,
+        result.total_return_pct()
+    );
+
     let chart = Chart::new(datasets)
-        .block(Block::default().title("Gráfico de Bollinger Bands").borders(Borders::ALL))
-        .x_axis(create_x_axis(data))
-        .y_axis(create_y_axis(data));
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .x_axis(create_x_axis(&dates))
+        .y_axis(value_axis("Capital", AxisDomain::Price, bounds, rows));
 
     f.render_widget(chart, area);
     Ok(())
 }
 
-fn draw_momentum_view<B: Backend>(
+// Resumen del backtest y sus operaciones, de la más reciente a la más antigua
+fn create_backtest_panel(symbol: &Symbol, result: &BacktestResult) -> Paragraph<'static> {
+    let label = Style::default().fg(Color::Gray);
+    let mut lines = vec![
+        Line::from(vec![Span::styled("Capital inicial: ", label), Span::raw(format!("${:.2}", result.capital))]),
+        Line::from(vec![Span::styled("Capital final: ", label), Span::raw(format!("${:.2}", result.final_equity()))]),
+        Line::from(vec![Span::styled("Rentabilidad: ", label), Span::raw(format!("{:+.2}%", result.total_return_pct()))]),
+        Line::from(vec![Span::styled("Máx. caída: ", label), Span::raw(format!("-{:.2}%", result.max_drawdown_pct()))]),
+        Line::from(vec![
+            Span::styled("Operaciones: ", label),
+            Span::raw(match result.win_rate() {
+                Some(rate) => format!("{} ({:.0}% ganadoras)", result.trades.len(), rate),
+                None => "ninguna".to_string(),
+            }),
+        ]),
+    ];
+    let open = result.open_position;
+    if open.quantity != 0.0 {
+        lines.push(Line::from(vec![
+            Span::styled("Abierta: ", label),
+            Span::raw(format!("{:.4} desde {} a ${:.2}", open.quantity, symbol.bars[open.entry_index].date, open.entry_price)),
+        ]));
+    }
+    lines.push(Line::from(""));
+
+    for trade in result.trades.iter().rev() {
+        let color = if trade.profit() > 0.0 { Color::Green } else { Color::Red };
+        lines.push(Line::from(vec![
+            Span::raw(format!(
+                "{} → {} ",
+                symbol.bars[trade.entry_index].date,
+                symbol.bars[trade.exit_index].date
+            )),
+            Span::styled(format!("{:+.2}%", trade.return_pct()), Style::default().fg(color)),
+        ]));
+    }
+
+    Paragraph::new(lines).block(Block::default().title("Backtest").borders(Borders::ALL))
+}
+
+fn get_correlation_color(value: f64) -> Color {
+    if value.is_nan() {
+        Color::DarkGray
+    } else if value >= 0.7 {
+        Color::Green
+    } else if value >= 0.3 {
+        Color::LightGreen
+    } else if value > -0.3 {
+        Color::Gray
+    } else if value > -0.7 {
+        Color::LightRed
+    } else {
+        Color::Red
+    }
+}
+
+// Símbolo de referencia para la correlación móvil; nunca el propio símbolo seleccionado
+pub fn benchmark_index(workspace: &Workspace, settings: &CorrelationSettings) -> usize {
+    let n = workspace.symbols.len();
+    let benchmark = settings.benchmark % n;
+    if benchmark == workspace.selected {
+        (benchmark + 1) % n
+    } else {
+        benchmark
+    }
+}
+
+fn draw_correlation_view<B: Backend>(
     f: &mut Frame<B>,
-    data: &[(String, f64)],
+    workspace: &Workspace,
+    settings: &CorrelationSettings,
     area: Rect,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let data_vec: Vec<(String, f64)> = data.to_vec();
-    let momentum_value = calculate_momentum(&data_vec, 14);
-    let momentum_data: Vec<(f64, f64)> = data.iter()
-        .enumerate()
-        .map(|(i, _)| (i as f64, momentum_value))
+    if workspace.symbols.len() < 2 {
+        let empty = Paragraph::new("Se necesitan al menos dos símbolos cargados")
+            .block(Block::default().title("Correlación").borders(Borders::ALL));
+        f.render_widget(empty, area);
+        return Ok(());
+    }
+
+    let n = workspace.symbols.len();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length((n as u16 + 3).min(area.height / 2)),
+            Constraint::Percentage(50),
+            Constraint::Percentage(50),
+        ])
+        .split(area);
+
+    // Matriz de correlación de retornos diarios
+    let matrix = workspace.correlation_matrix(settings.method);
+    let header = Row::new(
+        std::iter::once(Cell::from(""))
+            .chain(workspace.symbols.iter().map(|s| Cell::from(s.name.clone())))
+            .collect::<Vec<_>>(),
+    )
+    .style(Style::default().fg(Color::Yellow));
+    let rows: Vec<Row> = workspace
+        .symbols
+        .iter()
+        .zip(matrix.iter())
+        .map(|(symbol, values)| {
+            let cells = std::iter::once(Cell::from(symbol.name.clone()).style(Style::default().fg(Color::Yellow)))
+                .chain(values.iter().map(|v| {
+                    Cell::from(format!("{:+.2}", v)).style(Style::default().fg(get_correlation_color(*v)))
+                }));
+            Row::new(cells.collect::<Vec<_>>())
+        })
         .collect();
+    let widths = vec![Constraint::Length(12); n + 1];
+    let table = Table::new(rows)
+        .header(header)
+        .widths(&widths)
+        .block(Block::default()
+            .title(format!("Correlación de retornos diarios ({})", settings.method.as_str()))
+            .borders(Borders::ALL));
+    f.render_widget(table, chunks[0]);
+
+    // Correlación y beta móviles del símbolo seleccionado frente a la referencia
+    let asset = workspace.current();
+    let reference = benchmark_index(workspace, settings);
+    let benchmark = &workspace.symbols[reference];
+    let series = workspace.rolling_correlation(reference, settings);
+    let dates: Vec<(String, f64)> = series.iter().map(|(d, _, _)| (d.to_string(), 0.0)).collect();
+    let correlation_data: Vec<(f64, f64)> = series.iter().enumerate().map(|(i, (_, c, _))| (i as f64, *c)).collect();
+    let beta_data: Vec<(f64, f64)> = series.iter().enumerate().map(|(i, (_, _, b))| (i as f64, *b)).collect();
+
+    if series.is_empty() {
+        let empty = Paragraph::new(format!("Menos de {} retornos en común", settings.window))
+            .block(Block::default().title("Correlación móvil").borders(Borders::ALL));
+        f.render_widget(empty, chunks[1]);
+        return Ok(());
+    }
 
-    let datasets = vec![Dataset::default()
-        .name("Momentum")
+    let correlation_chart = Chart::new(vec![Dataset::default()
+        .name(format!("{} vs {}", asset.name, benchmark.name))
         .marker(symbols::Marker::Braille)
         .style(Style::default().fg(Color::Cyan))
-        .data(&momentum_data)
-    ];
-
-    let chart = Chart::new(datasets)
-        .block(Block::default().title("Gráfico de Momentum").borders(Borders::ALL))
-        .x_axis(create_x_axis(data))
-        .y_axis(create_y_axis(data));
+        .data(&correlation_data)])
+        .block(Block::default()
+            .title(format!("Correlación móvil {} ({} días)", settings.method.as_str(), settings.window))
+            .borders(Borders::ALL))
+        .x_axis(create_x_axis(&dates))
+        .y_axis(Axis::default()
+            .style(Style::default().fg(Color::Gray))
+            .bounds([-1.0, 1.0])
+            .labels(["-1", "0", "1"].iter().map(|l| Span::styled(*l, Style::default().fg(Color::Gray))).collect()));
+    f.render_widget(correlation_chart, chunks[1]);
+
+    let betas = beta_data.iter().map(|(_, b)| *b).filter(|b| b.is_finite());
+    let max_beta = betas.clone().fold(f64::NEG_INFINITY, f64::max).max(1.0);
+    let min_beta = betas.fold(f64::INFINITY, f64::min).min(0.0);
+    let beta_chart = Chart::new(vec![Dataset::default()
+        .name(format!("Beta {} / {}", asset.name, benchmark.name))
+        .marker(symbols::Marker::Braille)
+        .style(Style::default().fg(Color::Magenta))
+        .data(&beta_data)])
+        .block(Block::default()
+            .title(format!("Beta móvil ({} días)", settings.window))
+            .borders(Borders::ALL))
+        .x_axis(create_x_axis(&dates))
+        .y_axis(Axis::default()
+            .style(Style::default().fg(Color::Gray))
+            .bounds([min_beta, max_beta])
+            .labels(vec![
+                Span::styled(format!("{:.2}", min_beta), Style::default().fg(Color::Gray)),
+                Span::styled(format!("{:.2}", max_beta), Style::default().fg(Color::Gray)),
+            ]));
+    f.render_widget(beta_chart, chunks[2]);
 
-    f.render_widget(chart, area);
     Ok(())
 }
@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::draw::Candle;
+use crate::fechas::Fecha;
+
+// Punto de un dibujo: fecha de la barra y precio. Con la fecha el dibujo sobrevive a los
+// cambios de temporalidad y a las recargas del fichero.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub fecha: String,
+    pub precio: f64,
+}
+
+impl Anchor {
+    // Posición de la barra que contiene la fecha, o None si la fecha no se entiende
+    pub fn bar_index(&self, bars: &[Candle]) -> Option<usize> {
+        let date = Fecha::parse(&self.fecha)?;
+        let index = bars.partition_point(|c| Fecha::parse(&c.date).is_none_or(|f| f <= date));
+        Some(index.saturating_sub(1))
+    }
+}
+
+// Anotación sobre el gráfico de un símbolo, tal como se guarda en su fichero de dibujos
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "tipo", rename_all = "lowercase")]
+pub enum Drawing {
+    // Recta entre dos barras
+    Tendencia { desde: Anchor, hasta: Anchor },
+    // Nivel de precio horizontal en todo el gráfico
+    Nivel { precio: f64 },
+    Rectangulo { desde: Anchor, hasta: Anchor },
+    Nota { en: Anchor, texto: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Trendline,
+    Level,
+    Rectangle,
+    Note,
+}
+
+impl Tool {
+    // Recorre las herramientas; tras la última se sale del modo de dibujo
+    pub fn next(tool: Option<Tool>) -> Option<Tool> {
+        match tool {
+            None => Some(Tool::Trendline),
+            Some(Tool::Trendline) => Some(Tool::Level),
+            Some(Tool::Level) => Some(Tool::Rectangle),
+            Some(Tool::Rectangle) => Some(Tool::Note),
+            Some(Tool::Note) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Tool::Trendline => "Tendencia",
+            Tool::Level => "Nivel",
+            Tool::Rectangle => "Rectángulo",
+            Tool::Note => "Nota",
+        }
+    }
+
+    // Puntos que hay que marcar para completar el dibujo
+    pub fn points(&self) -> usize {
+        match self {
+            Tool::Trendline | Tool::Rectangle => 2,
+            Tool::Level | Tool::Note => 1,
+        }
+    }
+}
+
+// Dibujo en curso: herramienta elegida, puntos ya marcados y, en las notas, el texto que se
+// está escribiendo
+#[derive(Debug, Clone)]
+pub struct Sketch {
+    pub tool: Tool,
+    pub points: Vec<Anchor>,
+    pub text: Option<String>,
+}
+
+impl Sketch {
+    pub fn new(tool: Tool) -> Self {
+        Sketch { tool, points: Vec::new(), text: None }
+    }
+
+    // Marca un punto y devuelve el dibujo si queda completo. Una nota pasa antes a pedir su texto.
+    pub fn add_point(&mut self, anchor: Anchor) -> Option<Drawing> {
+        if self.text.is_some() {
+            return None;
+        }
+        self.points.push(anchor);
+        if self.points.len() < self.tool.points() {
+            return None;
+        }
+        if self.tool == Tool::Note {
+            self.text = Some(String::new());
+            return None;
+        }
+        let mut points = std::mem::take(&mut self.points).into_iter();
+        let desde = points.next()?;
+        match self.tool {
+            Tool::Trendline => Some(Drawing::Tendencia { desde, hasta: points.next()? }),
+            Tool::Rectangle => Some(Drawing::Rectangulo { desde, hasta: points.next()? }),
+            _ => Some(Drawing::Nivel { precio: desde.precio }),
+        }
+    }
+
+    // Cierra la nota con el texto escrito; una nota vacía se descarta
+    pub fn finish_text(&mut self) -> Option<Drawing> {
+        let texto = self.text.take()?;
+        let en = self.points.pop()?;
+        self.points.clear();
+        (!texto.trim().is_empty()).then_some(Drawing::Nota { en, texto })
+    }
+}
+
+// Fichero de dibujos junto al de datos: `datos.csv` -> `datos.dibujos.json`
+pub fn sidecar_path(file_path: &str) -> PathBuf {
+    Path::new(file_path).with_extension("dibujos.json")
+}
+
+// Al cargar un directorio los ficheros de dibujos no son series de datos
+pub fn is_sidecar(path: &Path) -> bool {
+    path.to_str().is_some_and(|p| p.ends_with(".dibujos.json"))
+}
+
+// Un símbolo sin fichero de dibujos empieza sin ninguno
+pub fn load_drawings(path: &Path) -> Result<Vec<Drawing>, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+// Sin dibujos se borra el fichero en lugar de dejar una lista vacía
+pub fn save_drawings(path: &Path, drawings: &[Drawing]) -> Result<(), Box<dyn Error>> {
+    if drawings.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    fs::write(path, serde_json::to_string_pretty(drawings)?)?;
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+
+// Fecha de calendario sin hora, suficiente para series diarias
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fecha {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Fecha {
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Fecha { year, month, day })
+    }
+
+    // Acepta "dd.mm.yyyy" (investing.com) y "yyyy-mm-dd"
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Some((d, rest)) = value.split_once('.') {
+            let (m, y) = rest.split_once('.')?;
+            return Fecha::new(y.parse().ok()?, m.parse().ok()?, d.parse().ok()?);
+        }
+        let mut parts = value.splitn(3, '-');
+        let y = parts.next()?.parse().ok()?;
+        let m = parts.next()?.parse().ok()?;
+        let d = parts.next()?.parse().ok()?;
+        Fecha::new(y, m, d)
+    }
+
+    // Días desde 1970-01-01 (algoritmo days_from_civil de H. Hinnant)
+    pub fn days(&self) -> i64 {
+        let y = (if self.month <= 2 { self.year - 1 } else { self.year }) as i64;
+        let m = self.month as i64;
+        let era = (if y >= 0 { y } else { y - 399 }) / 400;
+        let yoe = y - era * 400;
+        let mp = (m + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    pub fn from_days(days: i64) -> Self {
+        let z = days + 719468;
+        let era = (if z >= 0 { z } else { z - 146096 }) / 146097;
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+        let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+        Fecha { year, month, day }
+    }
+
+    // 0 = lunes ... 6 = domingo
+    pub fn weekday(&self) -> u32 {
+        // 1970-01-01 fue jueves
+        (self.days() + 3).rem_euclid(7) as u32
+    }
+}
+
+impl fmt::Display for Fecha {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}.{:02}.{:04}", self.day, self.month, self.year)
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
@@ -0,0 +1,649 @@
+use std::collections::VecDeque;
+
+use crate::draw::Candle;
+
+// Indicador con estado que se actualiza barra a barra en O(1). Sus valores coinciden con las
+// funciones por lotes de `predicion` aplicadas a las mismas barras.
+pub trait Indicator {
+    type Output;
+
+    fn update(&mut self, bar: &Candle);
+    fn value(&self) -> Self::Output;
+}
+
+#[derive(Debug, Clone)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+    // Barras desde que se rehízo la suma
+    since_sum: usize,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        Sma {
+            period,
+            window: VecDeque::with_capacity(period + 1),
+            sum: 0.0,
+            since_sum: 0,
+        }
+    }
+}
+
+impl Indicator for Sma {
+    type Output = f64;
+
+    fn update(&mut self, bar: &Candle) {
+        self.window.push_back(bar.close);
+        self.sum += bar.close;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap_or(0.0);
+        }
+        // Con precios altos los redondeos de sumar y restar se acumulan en una serie larga;
+        // cada `period` barras la suma se rehace desde la ventana
+        self.since_sum += 1;
+        if self.since_sum >= self.period {
+            self.sum = self.window.iter().sum();
+            self.since_sum = 0;
+        }
+    }
+
+    // Con menos de `period` barras se devuelve el último cierre
+    fn value(&self) -> f64 {
+        if self.window.len() < self.period {
+            return self.window.back().copied().unwrap_or(f64::NAN);
+        }
+        self.sum / self.period as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Ema {
+    multiplier: f64,
+    ema: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        Ema {
+            multiplier: 2.0 / (period + 1) as f64,
+            ema: None,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        self.ema = Some(match self.ema {
+            Some(ema) => (value - ema) * self.multiplier + ema,
+            None => value,
+        });
+    }
+}
+
+impl Indicator for Ema {
+    type Output = f64;
+
+    fn update(&mut self, bar: &Candle) {
+        self.push(bar.close);
+    }
+
+    fn value(&self) -> f64 {
+        self.ema.unwrap_or(f64::NAN)
+    }
+}
+
+// RSI de Wilder: media simple de las primeras `period` variaciones y suavizado después
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    last_close: Option<f64>,
+    changes: usize,
+    avg_gain: f64,
+    avg_loss: f64,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Rsi {
+            period,
+            last_close: None,
+            changes: 0,
+            avg_gain: 0.0,
+            avg_loss: 0.0,
+        }
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = f64;
+
+    fn update(&mut self, bar: &Candle) {
+        if let Some(last_close) = self.last_close {
+            let diff = bar.close - last_close;
+            let (gain, loss) = if diff >= 0.0 { (diff, 0.0) } else { (0.0, diff.abs()) };
+            self.changes += 1;
+            let period = self.period as f64;
+            if self.changes <= self.period {
+                // Mientras se completa la primera ventana se acumulan las sumas
+                self.avg_gain += gain;
+                self.avg_loss += loss;
+                if self.changes == self.period {
+                    self.avg_gain /= period;
+                    self.avg_loss /= period;
+                }
+            } else {
+                self.avg_gain = (self.avg_gain * (period - 1.0) + gain) / period;
+                self.avg_loss = (self.avg_loss * (period - 1.0) + loss) / period;
+            }
+        }
+        self.last_close = Some(bar.close);
+    }
+
+    fn value(&self) -> f64 {
+        if self.changes < self.period {
+            return 50.0;
+        }
+        if self.avg_loss == 0.0 {
+            return 100.0;
+        }
+        let rs = self.avg_gain / self.avg_loss;
+        100.0 - (100.0 / (1.0 + rs))
+    }
+}
+
+// MACD (12, 26) con su línea de señal EMA(9)
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+}
+
+impl Default for Macd {
+    fn default() -> Self {
+        Macd {
+            fast: Ema::new(12),
+            slow: Ema::new(26),
+            signal: Ema::new(9),
+        }
+    }
+}
+
+impl Indicator for Macd {
+    // (MACD, señal)
+    type Output = (f64, f64);
+
+    fn update(&mut self, bar: &Candle) {
+        self.fast.update(bar);
+        self.slow.update(bar);
+        self.signal.push(self.fast.value() - self.slow.value());
+    }
+
+    fn value(&self) -> (f64, f64) {
+        (self.fast.value() - self.slow.value(), self.signal.value())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    sma: Sma,
+}
+
+impl Bollinger {
+    pub fn new(period: usize) -> Self {
+        Bollinger { sma: Sma::new(period) }
+    }
+}
+
+impl Indicator for Bollinger {
+    // (superior, media, inferior)
+    type Output = (f64, f64, f64);
+
+    fn update(&mut self, bar: &Candle) {
+        self.sma.update(bar);
+    }
+
+    // Desviación típica de la ventana respecto a la media, dividida por `period` aunque la
+    // ventana aún no esté completa. Se recorre la ventana en lugar de llevar la suma de
+    // cuadrados: a precios altos esa suma menos el cuadrado de la media pierde casi todas sus
+    // cifras.
+    fn value(&self) -> (f64, f64, f64) {
+        let sma = self.sma.value();
+        let deviation: f64 = self.sma.window.iter().map(|x| (x - sma).powi(2)).sum();
+        let std_dev = (deviation / self.sma.period as f64).sqrt();
+        (sma + (2.0 * std_dev), sma, sma - (2.0 * std_dev))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Momentum {
+    period: usize,
+    window: VecDeque<f64>,
+}
+
+impl Momentum {
+    pub fn new(period: usize) -> Self {
+        Momentum {
+            period,
+            window: VecDeque::with_capacity(period + 2),
+        }
+    }
+}
+
+impl Indicator for Momentum {
+    type Output = f64;
+
+    fn update(&mut self, bar: &Candle) {
+        self.window.push_back(bar.close);
+        if self.window.len() > self.period + 1 {
+            self.window.pop_front();
+        }
+    }
+
+    // Variación porcentual frente al cierre de hace `period` barras
+    fn value(&self) -> f64 {
+        match (self.window.front(), self.window.back()) {
+            (Some(past), Some(current)) if self.window.len() > self.period => {
+                ((current - past) / past) * 100.0
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+// Rango verdadero medio de Wilder: media simple de los primeros `period` rangos y suavizado después
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+    last_close: Option<f64>,
+    count: usize,
+    atr: f64,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Atr {
+            period,
+            last_close: None,
+            count: 0,
+            atr: 0.0,
+        }
+    }
+}
+
+impl Indicator for Atr {
+    type Output = f64;
+
+    fn update(&mut self, bar: &Candle) {
+        let true_range = match self.last_close {
+            Some(close) => (bar.high - bar.low).max((bar.high - close).abs()).max((bar.low - close).abs()),
+            None => bar.high - bar.low,
+        };
+        self.count += 1;
+        if self.count <= self.period {
+            self.atr += (true_range - self.atr) / self.count as f64;
+        } else {
+            let period = self.period as f64;
+            self.atr = (self.atr * (period - 1.0) + true_range) / period;
+        }
+        self.last_close = Some(bar.close);
+    }
+
+    fn value(&self) -> f64 {
+        if self.count == 0 {
+            return f64::NAN;
+        }
+        self.atr
+    }
+}
+
+// Canal de Keltner: EMA de los cierres ± 2 ATR(10)
+#[derive(Debug, Clone)]
+pub struct Keltner {
+    ema: Ema,
+    atr: Atr,
+}
+
+impl Keltner {
+    pub fn new(period: usize) -> Self {
+        Keltner {
+            ema: Ema::new(period),
+            atr: Atr::new(10),
+        }
+    }
+}
+
+impl Indicator for Keltner {
+    // (superior, media, inferior)
+    type Output = (f64, f64, f64);
+
+    fn update(&mut self, bar: &Candle) {
+        self.ema.update(bar);
+        self.atr.update(bar);
+    }
+
+    fn value(&self) -> (f64, f64, f64) {
+        let middle = self.ema.value();
+        let width = 2.0 * self.atr.value();
+        (middle + width, middle, middle - width)
+    }
+}
+
+// SAR parabólico de Wilder con aceleración 0.02 hasta 0.2
+#[derive(Debug, Clone, Default)]
+pub struct ParabolicSar {
+    // Máximo y mínimo de las dos barras anteriores, la más reciente primero
+    previous: Vec<(f64, f64)>,
+    first_close: Option<f64>,
+    sar: Option<f64>,
+    long: bool,
+    extreme: f64,
+    acceleration: f64,
+}
+
+const SAR_STEP: f64 = 0.02;
+const SAR_MAX: f64 = 0.2;
+
+impl Indicator for ParabolicSar {
+    type Output = f64;
+
+    fn update(&mut self, bar: &Candle) {
+        match (self.sar, self.first_close) {
+            (None, None) => self.first_close = Some(bar.close),
+            // La segunda barra decide la tendencia inicial
+            (None, Some(first_close)) => {
+                let (high, low) = self.previous[0];
+                self.long = bar.close >= first_close;
+                self.sar = Some(if self.long { low.min(bar.low) } else { high.max(bar.high) });
+                self.extreme = if self.long { bar.high } else { bar.low };
+                self.acceleration = SAR_STEP;
+            }
+            (Some(sar), _) => {
+                let mut next = sar + self.acceleration * (self.extreme - sar);
+                // El SAR nunca entra en el rango de las dos barras anteriores
+                for (high, low) in &self.previous {
+                    next = if self.long { next.min(*low) } else { next.max(*high) };
+                }
+                if (self.long && bar.low < next) || (!self.long && bar.high > next) {
+                    // Giro: el SAR salta al extremo de la tendencia que termina
+                    next = self.extreme;
+                    self.long = !self.long;
+                    self.extreme = if self.long { bar.high } else { bar.low };
+                    self.acceleration = SAR_STEP;
+                } else if (self.long && bar.high > self.extreme) || (!self.long && bar.low < self.extreme) {
+                    self.extreme = if self.long { bar.high } else { bar.low };
+                    self.acceleration = (self.acceleration + SAR_STEP).min(SAR_MAX);
+                }
+                self.sar = Some(next);
+            }
+        }
+        self.previous.insert(0, (bar.high, bar.low));
+        self.previous.truncate(2);
+    }
+
+    fn value(&self) -> f64 {
+        self.sar.unwrap_or(f64::NAN)
+    }
+}
+
+// Precio medio ponderado por volumen desde la primera barra recibida
+#[derive(Debug, Clone, Default)]
+pub struct Vwap {
+    price_volume: f64,
+    volume: f64,
+}
+
+impl Indicator for Vwap {
+    type Output = f64;
+
+    fn update(&mut self, bar: &Candle) {
+        let typical = (bar.high + bar.low + bar.close) / 3.0;
+        self.price_volume += typical * bar.volume;
+        self.volume += bar.volume;
+    }
+
+    fn value(&self) -> f64 {
+        if self.volume > 0.0 {
+            self.price_volume / self.volume
+        } else {
+            f64::NAN
+        }
+    }
+}
+
+// Máximo, mínimo, media y último cierre de la serie
+#[derive(Debug, Clone)]
+pub struct Stats {
+    pub max: f64,
+    pub min: f64,
+    sum: f64,
+    count: usize,
+    pub last: f64,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Stats {
+            max: f64::NEG_INFINITY,
+            min: f64::INFINITY,
+            sum: 0.0,
+            count: 0,
+            last: 0.0,
+        }
+    }
+}
+
+impl Stats {
+    pub fn average(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+
+    fn update(&mut self, bar: &Candle) {
+        self.max = self.max.max(bar.close);
+        self.min = self.min.min(bar.close);
+        self.sum += bar.close;
+        self.count += 1;
+        self.last = bar.close;
+    }
+}
+
+// Valores de todos los indicadores del panel en una barra
+#[derive(Debug, Clone, Copy)]
+pub struct IndicatorValues {
+    pub sma: f64,
+    pub rsi: f64,
+    pub macd: (f64, f64),
+    pub bollinger: (f64, f64, f64),
+    pub momentum: f64,
+}
+
+// Indicadores del panel de estadísticas, con los mismos parámetros que usa la interfaz
+#[derive(Debug, Clone)]
+pub struct IndicatorSet {
+    pub stats: Stats,
+    pub sma: Sma,
+    pub rsi: Rsi,
+    pub macd: Macd,
+    pub bollinger: Bollinger,
+    pub momentum: Momentum,
+    len: usize,
+}
+
+impl Default for IndicatorSet {
+    fn default() -> Self {
+        IndicatorSet {
+            stats: Stats::default(),
+            sma: Sma::new(20),
+            rsi: Rsi::new(14),
+            macd: Macd::default(),
+            bollinger: Bollinger::new(20),
+            momentum: Momentum::new(14),
+            len: 0,
+        }
+    }
+}
+
+impl IndicatorSet {
+    pub fn update(&mut self, bar: &Candle) {
+        self.stats.update(bar);
+        self.sma.update(bar);
+        self.rsi.update(bar);
+        self.macd.update(bar);
+        self.bollinger.update(bar);
+        self.momentum.update(bar);
+        self.len += 1;
+    }
+
+    pub fn values(&self) -> IndicatorValues {
+        IndicatorValues {
+            sma: self.sma.value(),
+            rsi: self.rsi.value(),
+            macd: self.macd.value(),
+            bollinger: self.bollinger.value(),
+            momentum: self.momentum.value(),
+        }
+    }
+
+    // Número de barras procesadas
+    pub fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predicion::{
+        calculate_bollinger_bands, calculate_ema, calculate_macd, calculate_momentum, calculate_rsi, calculate_sma,
+    };
+
+    // Cierres con subidas, bajadas, tramos planos y un cierre repetido
+    const CLOSES: [f64; 48] = [
+        100.0, 101.5, 100.8, 102.3, 103.0, 102.1, 101.4, 101.4, 103.9, 105.2, 104.7, 106.0, 105.1, 103.8, 104.4,
+        106.9, 108.2, 107.5, 107.5, 109.1, 110.4, 108.8, 107.2, 106.5, 105.0, 106.3, 108.7, 109.9, 111.2, 110.6,
+        112.0, 113.4, 112.7, 111.9, 110.2, 109.8, 111.5, 113.0, 114.6, 113.8, 115.1, 116.4, 115.0, 113.7, 114.9,
+        116.2, 117.8, 117.1,
+    ];
+
+    fn bars() -> Vec<Candle> {
+        bars_at(0.0)
+    }
+
+    // Los mismos cierres desplazados `level`, p. ej. a precios de BTC
+    fn bars_at(level: f64) -> Vec<Candle> {
+        CLOSES
+            .iter()
+            .map(|close| close + level)
+            .enumerate()
+            .map(|(i, close)| Candle {
+                date: format!("{:02}.01.2024", i + 1),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 1.0,
+                gap: false,
+            })
+            .collect()
+    }
+
+    // Serie de unas 2000 barras que sube y baja sobre `level`, para que los indicadores con
+    // sumas acumuladas desplacen su ventana muchas veces
+    fn long_bars(level: f64) -> Vec<Candle> {
+        let bars = bars_at(level);
+        let back: Vec<Candle> = bars.iter().rev().cloned().collect();
+        bars.iter().chain(&back).cycle().take(2000).cloned().collect()
+    }
+
+    fn data(bars: &[Candle]) -> Vec<(String, f64)> {
+        bars.iter().map(|bar| (bar.date.clone(), bar.close)).collect()
+    }
+
+    fn assert_close(incremental: f64, batch: f64, index: usize) {
+        assert!(
+            (incremental - batch).abs() < 1e-9,
+            "barra {}: incremental {} frente a {} por lotes",
+            index,
+            incremental,
+            batch
+        );
+    }
+
+    #[test]
+    fn sma_matches_batch() {
+        for level in [0.0, 100_000.0] {
+            let bars = long_bars(level);
+            let data = data(&bars);
+            let mut sma = Sma::new(20);
+            for (i, bar) in bars.iter().enumerate() {
+                sma.update(bar);
+                assert_close(sma.value(), calculate_sma(&data[..=i], 20), i);
+            }
+        }
+    }
+
+    // Con precios altos las sumas de cuadrados pierden precisión al restarse
+    #[test]
+    fn bollinger_matches_batch() {
+        for level in [0.0, 100_000.0] {
+            let bars = long_bars(level);
+            let data = data(&bars);
+            let mut bollinger = Bollinger::new(20);
+            for (i, bar) in bars.iter().enumerate() {
+                bollinger.update(bar);
+                let (upper, middle, lower) = bollinger.value();
+                let (batch_upper, batch_middle, batch_lower) = calculate_bollinger_bands(&data[..=i], 20);
+                assert_close(upper, batch_upper, i);
+                assert_close(middle, batch_middle, i);
+                assert_close(lower, batch_lower, i);
+            }
+        }
+    }
+
+    #[test]
+    fn rsi_matches_batch() {
+        let bars = bars();
+        let data = data(&bars);
+        let mut rsi = Rsi::new(14);
+        for (i, bar) in bars.iter().enumerate() {
+            rsi.update(bar);
+            assert_close(rsi.value(), calculate_rsi(&data[..=i], 14), i);
+        }
+    }
+
+    #[test]
+    fn ema_matches_batch() {
+        let bars = bars();
+        let mut ema = Ema::new(12);
+        for (i, bar) in bars.iter().enumerate() {
+            ema.update(bar);
+            let batch = calculate_ema(&CLOSES[..=i], 12);
+            assert_close(ema.value(), batch[i], i);
+        }
+    }
+
+    #[test]
+    fn macd_matches_batch() {
+        let bars = bars();
+        let data = data(&bars);
+        let mut macd = Macd::default();
+        for (i, bar) in bars.iter().enumerate() {
+            macd.update(bar);
+            let (line, signal) = macd.value();
+            let (batch_line, batch_signal) = calculate_macd(&data[..=i]);
+            assert_close(line, batch_line, i);
+            assert_close(signal, batch_signal, i);
+        }
+    }
+
+    #[test]
+    fn momentum_matches_batch() {
+        let bars = bars();
+        let data = data(&bars);
+        let mut momentum = Momentum::new(14);
+        for (i, bar) in bars.iter().enumerate() {
+            momentum.update(bar);
+            assert_close(momentum.value(), calculate_momentum(&data[..=i], 14), i);
+        }
+    }
+}
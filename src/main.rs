@@ -1,132 +1,318 @@
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 use std::error::Error;
 use std::io;
-use std::path::Path;
 use std::env;
 
+mod ajustes;
+mod alerts;
+mod axis;
+mod backtest;
+mod cache;
+mod comparacion;
+mod config;
+mod correlacion;
 mod draw;
+mod drawings;
+mod fechas;
+mod indicadores;
+mod mouse;
+mod overlays;
+mod panes;
 mod predicion;
+mod replay;
+mod resample;
 mod serde_data;
-use crate::draw::{draw_chart, ChartType};
-use crate::predicion::{predict_price, predict_price_moving_average};
-use crate::serde_data::load_data_from_csv;
+mod signals;
+mod stream;
+mod validacion;
+mod viewport;
+mod watch;
+mod workspace;
+use crate::ajustes::load_adjustments;
+use crate::alerts::{load_alert_rules, AlertMonitor};
+use crate::config::Config;
+use crate::comparacion::shift_base_date;
+use crate::correlacion::export_matrix_csv;
+use crate::draw::{benchmark_index, draw_chart, ChartType, ViewState};
+use crate::drawings::{Sketch, Tool};
+use crate::overlays::Overlay;
+use crate::replay::Replay;
+use crate::workspace::Workspace;
+
+// Alertas que se conservan en la barra de notificaciones
+const MAX_NOTIFICATIONS: usize = 50;
 
 fn main() -> Result<(), Box<dyn Error>> {
     // Imprimir el directorio de trabajo actual
     let cwd = env::current_dir()?;
     println!("Directorio de trabajo actual: {:?}", cwd);
 
-    // Cargar los datos desde el CSV
-    let file_path = "data.csv"; // Asegúrate de que esta ruta sea correcta
-    if !Path::new(file_path).exists() {
-        eprintln!("Error: El archivo {} no existe.", file_path);
-        return Ok(());
-    }
-
-    let data = load_data_from_csv(file_path)?;
+    let config = Config::from_args(env::args())?;
 
-    let past_days: Vec<f64> = data.iter().map(|d| d.apertura * 1000.0).collect();
-    let future_data: Vec<f64> = data.iter().map(|d| d.ultimo * 1000.0).collect();
+    // Cargar cada instrumento desde su CSV o JSON
+    let adjustments = match &config.adjustments {
+        Some(adjustments_path) => load_adjustments(adjustments_path)?,
+        None => Vec::new(),
+    };
+    let mut workspace = Workspace::load(&config, &adjustments)?;
 
-    if past_days.is_empty() || future_data.is_empty() {
-        eprintln!("Error: No hay datos disponibles en el CSV.");
-        return Ok(());
+    let mut view = ViewState::default();
+    view.comparison.base_date = config.base_date;
+    if let Some(ndays) = config.ndays {
+        view.ndays = ndays;
     }
+    if let Some(window) = config.correlation_window {
+        view.correlation.window = window;
+    }
+    if let Some(capital) = config.capital {
+        view.backtest.capital = capital;
+    }
+    view.backtest.stop_loss = config.stop_loss;
+    view.backtest.take_profit = config.take_profit;
+    if let Some(start) = config.replay_start {
+        view.replay = Replay::start(&mut workspace, start);
+    }
+    let mut current_prediction = workspace.current_mut().prediction(&view.chart_type)?;
 
-    // Realizar la predicción inicial
-    let mut current_prediction = predict_price(98.0, past_days.clone(), future_data.clone(), true)?;
-
-    let mut using_linear = true;
-    let mut chart_type = ChartType::Line;
+    // Reglas de alerta, evaluadas en cada barra nueva
+    let mut alerts = match &config.alerts {
+        Some(rules_path) => {
+            let log_path = config.alert_log.clone().unwrap_or_else(|| "alertas.log".to_string());
+            Some(AlertMonitor::new(load_alert_rules(rules_path)?, log_path, config.webhook.clone()))
+        }
+        None => None,
+    };
 
-    // Crear datos para el gráfico con fechas
-    let mut chart_data: Vec<(String, f64)> = data
-        .iter()
-        .map(|d| (d.fecha.clone(), d.ultimo * 1000.0))
-        .collect();
-    // Ordenar los datos por fecha de más antigua a más reciente
-    chart_data.reverse();
+    // Hilo lector de barras en tiempo real, si se pidió
+    let stream = match &config.stream {
+        Some(source) => Some(stream::spawn(source.clone(), config.mapping.clone())?),
+        None => None,
+    };
 
     // Configurar terminal
     terminal::enable_raw_mode()?;
     let mut stdout = io::stdout();
-    crossterm::execute!(stdout, EnterAlternateScreen)?;
+    crossterm::execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
+    let mut drag = None;
+
     // Bucle principal que espera 'q' o Esc para salir
     loop {
-        let info = draw::create_info_panel(&chart_data);
+        // Recargar los ficheros que el exportador haya actualizado, sin tocar la vista
+        // Durante el replay no se recarga ni se añade nada, para no mostrar barras futuras
+        if config.watch && view.replay.is_none() {
+            let messages = workspace.poll_changes(&config, &adjustments);
+            if !messages.is_empty() {
+                view.status = Some(messages.join(" · "));
+                update_prediction(&mut workspace, &mut view, &mut current_prediction);
+            }
+        }
+
+        if let (Some(rx), None) = (&stream, &view.replay) {
+            if let Some(message) = stream::apply_pending(rx, &mut workspace, &config, &adjustments) {
+                view.status = Some(message);
+                update_prediction(&mut workspace, &mut view, &mut current_prediction);
+            }
+        }
+
+        if let Some(replay) = &mut view.replay {
+            if replay.tick(&mut workspace) {
+                update_prediction(&mut workspace, &mut view, &mut current_prediction);
+            }
+        }
+
+        if let Some(monitor) = &mut alerts {
+            view.notifications.extend(monitor.check(&workspace));
+            // Sólo se conservan las más recientes; el registro guarda todas
+            let excess = view.notifications.len().saturating_sub(MAX_NOTIFICATIONS);
+            view.notifications.drain(..excess);
+        }
+
         terminal.draw(|f| {
-            f.render_widget(info.clone(), f.size());
-            if let Err(e) = draw_chart(f, &chart_data, current_prediction, &chart_type) {
+            if let Err(e) = draw_chart(f, &workspace, current_prediction, &view) {
                 eprintln!("Error dibujando el gráfico: {}", e);
             }
         })?;
 
         if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => break,
-                    KeyCode::Char('t') => {
-                        // Cambiar entre predicciones
-                        chart_type = chart_type.next();
-                        match chart_type {
-                            ChartType::Line => {
-                                current_prediction = predict_price(
-                                    98.0,
-                                    past_days.clone(),
-                                    future_data.clone(),
-                                    true,
-                                )?;
-                            },
-                            ChartType::Candlestick => {
-                                current_prediction = predict_price(
-                                    98.0,
-                                    past_days.clone(),
-                                    future_data.clone(),
-                                    false,
-                                )?;
-                            },
-                            ChartType::MACD => {
-                                let (macd_line, _) = predicion::calculate_macd(&chart_data);
-                                current_prediction = macd_line;
-                            },
-                            ChartType::SMA => {
-                                current_prediction = predicion::calculate_sma(&chart_data, 20);
-                            },
-                            ChartType::RSI => {
-                                current_prediction = predicion::calculate_rsi(&chart_data, 14);
-                            },
-                            ChartType::BollingerBands => {
-                                let (upper, middle, _) = predicion::calculate_bollinger_bands(&chart_data, 20);
-                                current_prediction = middle;
-                            },
-                            ChartType::Momentum => {
-                                current_prediction = predicion::calculate_momentum(&chart_data, 14);
-                            },
-                            _ => {},
-                        }
-                        // Forzar un redibujo del gráfico
-                        terminal.draw(|f| {
-                            f.render_widget(info.clone(), f.size());
-                            if let Err(e) = draw_chart(f, &chart_data, current_prediction, &chart_type) {
-                                eprintln!("Error dibujando el gráfico: {}", e);
+            match event::read()? {
+                // Mientras se escribe una nota el teclado va al texto
+                Event::Key(key) if view.is_typing_note() => {
+                    if let Some(typed) = view.sketch.as_mut().and_then(|s| s.text.as_mut()) {
+                        match key.code {
+                            KeyCode::Char(c) => typed.push(c),
+                            KeyCode::Backspace => {
+                                typed.pop();
+                            }
+                            KeyCode::Enter => view.finish_note(&mut workspace),
+                            // Esc descarta la nota pero sigue con la herramienta de notas
+                            KeyCode::Esc => view.sketch = Some(Sketch::new(Tool::Note)),
+                            _ => {}
+                        }
+                    }
+                }
+                Event::Key(key) => {
+                    match key.code {
+                        // Esc sale primero del modo de dibujo
+                        KeyCode::Esc if view.sketch.is_some() => view.sketch = None,
+                        KeyCode::Char('q') | KeyCode::Esc => break,
+                        KeyCode::Char('t') => {
+                            view.chart_type = view.chart_type.next();
+                        }
+                        KeyCode::Char('r') => {
+                            // Cambiar la temporalidad y recalcular todo sobre la nueva serie
+                            let date = view.cursor_date(&workspace);
+                            view.timeframe = view.timeframe.next(view.ndays);
+                            workspace.set_timeframe(view.timeframe);
+                            view.set_cursor_date(&workspace, date);
+                            if let Some(monitor) = &mut alerts {
+                                monitor.mark_seen(&workspace);
+                            }
+                        }
+                        KeyCode::Char('n') => {
+                            view.comparison.normalization = view.comparison.normalization.next();
+                        }
+                        KeyCode::Char('[') => shift_base_date(&workspace, &mut view.comparison, -1),
+                        KeyCode::Char(']') => shift_base_date(&workspace, &mut view.comparison, 1),
+                        // Método, referencia y exportación de la vista de correlación
+                        KeyCode::Char('m') if view.chart_type == ChartType::Correlation => {
+                            view.correlation.method = view.correlation.method.next();
+                        }
+                        KeyCode::Char('b') if view.chart_type == ChartType::Correlation => {
+                            view.correlation.benchmark = benchmark_index(&workspace, &view.correlation) + 1;
+                        }
+                        KeyCode::Char('e') if view.chart_type == ChartType::Correlation => {
+                            let matrix = workspace.correlation_matrix(view.correlation.method);
+                            view.status = Some(match export_matrix_csv("correlacion.csv", &workspace, &matrix) {
+                                Ok(()) => "Matriz exportada a correlacion.csv".to_string(),
+                                Err(e) => format!("No se pudo exportar la matriz: {}", e),
+                            });
+                        }
+                        KeyCode::Char('v') => match view.replay.take() {
+                            Some(replay) => {
+                                replay.stop(&mut workspace);
+                                if let Some(monitor) = &mut alerts {
+                                    monitor.mark_seen(&workspace);
+                                }
+                            }
+                            None => {
+                                let start = config.replay_start.or(Replay::default_start(&workspace, 100));
+                                view.replay = start.and_then(|start| Replay::start(&mut workspace, start));
+                            }
+                        },
+                        KeyCode::Char('.') => {
+                            if let Some(replay) = &mut view.replay {
+                                replay.step_forward(&mut workspace);
+                            }
+                        }
+                        KeyCode::Char(',') => {
+                            if let Some(replay) = &mut view.replay {
+                                replay.step_back(&mut workspace);
+                            }
+                        }
+                        KeyCode::Char(' ') => {
+                            if let Some(replay) = &mut view.replay {
+                                replay.toggle_play();
+                            }
+                        }
+                        KeyCode::Char('>') => {
+                            if let Some(replay) = &mut view.replay {
+                                replay.faster();
+                            }
+                        }
+                        KeyCode::Char('<') => {
+                            if let Some(replay) = &mut view.replay {
+                                replay.slower();
                             }
-                        })?;
+                        }
+                        // Zoom y desplazamiento sobre la serie del símbolo seleccionado
+                        KeyCode::Char('+') | KeyCode::Char('=') => {
+                            view.viewport.zoom_in(&workspace.current().bars);
+                        }
+                        KeyCode::Char('-') => view.viewport.zoom_out(&workspace.current().bars),
+                        // Con el cursor activo las flechas lo mueven; si no, desplazan la ventana
+                        KeyCode::Left if view.cursor.is_some() => view.move_cursor(&workspace, -1),
+                        KeyCode::Right if view.cursor.is_some() => view.move_cursor(&workspace, 1),
+                        KeyCode::Left => view.viewport.pan(&workspace.current().bars, -1),
+                        KeyCode::Right => view.viewport.pan(&workspace.current().bars, 1),
+                        KeyCode::Char('c') => view.toggle_cursor(&workspace),
+                        // Superposiciones sobre el precio: 1 a 8 activan o quitan cada una
+                        KeyCode::Char(digit @ '1'..='8') => {
+                            let index = digit as usize - '1' as usize;
+                            view.toggle_overlay(Overlay::ALL[index]);
+                        }
+                        KeyCode::Char('l') => view.log_scale = !view.log_scale,
+                        KeyCode::Char('z') => view.notifications.clear(),
+                        // Señales de compra y venta: G las muestra, Re Pág y Av Pág recorren la lista
+                        KeyCode::Char('g') => view.signals = !view.signals,
+                        KeyCode::PageUp if view.signals => view.scroll_signals(&workspace, -10),
+                        KeyCode::PageDown if view.signals => view.scroll_signals(&workspace, 10),
+                        // Dibujos: D elige herramienta, Intro marca un punto en el cursor
+                        KeyCode::Char('d') => view.cycle_tool(),
+                        KeyCode::Enter if view.sketch.is_some() => {
+                            if let Some(anchor) = view.cursor_anchor(&workspace) {
+                                view.place_point(&mut workspace, anchor);
+                            }
+                        }
+                        // Retroceso sólo borra dibujos en modo de dibujo, sobre el eje de precio
+                        KeyCode::Backspace if view.sketch.is_some() && view.chart_type.is_price_view() => {
+                            view.remove_last_drawing(&mut workspace)
+                        }
+                        // Subpaneles de indicadores bajo el precio
+                        KeyCode::Char('a') => view.panes.add(),
+                        KeyCode::Char('x') => view.panes.remove_selected(),
+                        KeyCode::Char('p') => view.panes.select_next(),
+                        KeyCode::Up => view.panes.move_selected(-1),
+                        KeyCode::Down => view.panes.move_selected(1),
+                        KeyCode::Home => view.viewport.go_to_start(&workspace.current().bars),
+                        KeyCode::End => view.viewport.go_to_end(&workspace.current().bars),
+                        KeyCode::Char('0') => view.viewport.reset(),
+                        // Cambiar de instrumento conservando la vista y la temporalidad
+                        KeyCode::Tab => {
+                            let date = view.cursor_date(&workspace);
+                            workspace.select_next();
+                            view.set_cursor_date(&workspace, date);
+                        }
+                        KeyCode::BackTab => {
+                            let date = view.cursor_date(&workspace);
+                            workspace.select_previous();
+                            view.set_cursor_date(&workspace, date);
+                        }
+                        _ => {}
+                    }
+                    update_prediction(&mut workspace, &mut view, &mut current_prediction);
+                }
+                Event::Mouse(event) => {
+                    let size = terminal.size()?;
+                    // El ratón sólo cambia la predicción si se pulsó otra vista
+                    let changed = mouse::handle(event, size, &mut workspace, &mut view, &mut drag);
+                    if changed {
+                        update_prediction(&mut workspace, &mut view, &mut current_prediction);
                     }
-                    _ => {}
                 }
+                _ => {}
             }
         }
     }
 
     // Limpiar terminal
     terminal::disable_raw_mode()?;
-    crossterm::execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    crossterm::execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
 
     Ok(())
 }
+
+// Recalcula la predicción de la vista actual. Con el terminal en modo raw un error no puede
+// terminar el programa, que dejaría la pantalla sin restaurar: se avisa en la barra de estado y
+// se conserva la predicción anterior.
+fn update_prediction(workspace: &mut Workspace, view: &mut ViewState, prediction: &mut f64) {
+    match workspace.current_mut().prediction(&view.chart_type) {
+        Ok(value) => *prediction = value,
+        Err(e) => view.status = Some(format!("No se pudo calcular la predicción: {}", e)),
+    }
+}
@@ -0,0 +1,71 @@
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+
+use crate::draw::{self, ViewState};
+use crate::workspace::Workspace;
+
+// Arrastre en curso sobre el gráfico: columna donde empezó y desplazamiento de la ventana entonces
+#[derive(Debug, Clone, Copy)]
+pub struct Drag {
+    column: u16,
+    offset: usize,
+}
+
+// Aplica un evento del ratón a la vista. Devuelve true si cambió el tipo de gráfico, para
+// que se recalcule la predicción.
+pub fn handle(event: MouseEvent, size: Rect, workspace: &mut Workspace, view: &mut ViewState, drag: &mut Option<Drag>) -> bool {
+    match event.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            if let Some(chart_type) = draw::chart_type_at(size, workspace, view, event.column, event.row) {
+                let changed = chart_type != view.chart_type;
+                view.chart_type = chart_type;
+                return changed;
+            }
+            // Con una herramienta de dibujo activa, pulsar marca un punto del dibujo
+            if view.sketch.is_some() {
+                if let Some(anchor) = draw::anchor_at(size, workspace, view, event.column, event.row) {
+                    view.place_point(workspace, anchor);
+                }
+                return false;
+            }
+            // Pulsar sobre el gráfico selecciona la barra y empieza un posible arrastre
+            if let Some(index) = draw::bar_at(size, workspace, view, event.column, event.row) {
+                view.cursor = Some(index);
+                *drag = Some(Drag { column: event.column, offset: view.viewport.offset(&workspace.current().bars) });
+            }
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            if let (Some(start), Some((plot, range))) = (*drag, draw::plot_region(size, workspace, view)) {
+                // Arrastrar hacia la derecha trae barras anteriores
+                let bars_per_column = range.len() as f64 / plot.width as f64;
+                let moved = ((event.column as f64 - start.column as f64) * bars_per_column).round() as isize;
+                view.viewport.set_offset(&workspace.current().bars, start.offset.saturating_add_signed(moved));
+            }
+        }
+        MouseEventKind::Up(MouseButton::Left) => *drag = None,
+        MouseEventKind::Moved if view.cursor.is_some() => {
+            if let Some(index) = draw::bar_at(size, workspace, view, event.column, event.row) {
+                view.cursor = Some(index);
+            }
+        }
+        // La rueda sobre la lista de señales la desplaza en lugar de hacer zoom
+        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown
+            if draw::over_signals_panel(size, workspace, view, event.column, event.row) =>
+        {
+            let delta = if event.kind == MouseEventKind::ScrollUp { -1 } else { 1 };
+            view.scroll_signals(workspace, delta);
+        }
+        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+            if let (Some(index), Some((plot, _))) = (
+                draw::bar_at(size, workspace, view, event.column, event.row),
+                draw::plot_region(size, workspace, view),
+            ) {
+                let fraction = (event.column - plot.x) as f64 / plot.width as f64;
+                let zoom_in = event.kind == MouseEventKind::ScrollUp;
+                view.viewport.zoom_at(&workspace.current().bars, index, fraction, zoom_in);
+            }
+        }
+        _ => {}
+    }
+    false
+}
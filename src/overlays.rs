@@ -0,0 +1,173 @@
+use crate::axis::AxisDomain;
+use crate::draw::Candle;
+use crate::indicadores::{Bollinger, Ema, Indicator, Keltner, ParabolicSar, Rsi, Sma, Vwap};
+
+// Indicadores que se dibujan encima del gráfico de precio. Los que no están en escala de
+// precio, como el RSI, van en un eje secundario a la derecha: ver `domain`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Overlay {
+    Sma(usize),
+    Ema(usize),
+    Bollinger(usize),
+    Keltner(usize),
+    Sar,
+    // Anclado a la primera barra visible
+    Vwap,
+    Rsi(usize),
+}
+
+impl Overlay {
+    // Superposiciones disponibles, en el orden de las teclas 1 a 8
+    pub const ALL: [Overlay; 8] = [
+        Overlay::Sma(20),
+        Overlay::Ema(50),
+        Overlay::Sma(200),
+        Overlay::Bollinger(20),
+        Overlay::Keltner(20),
+        Overlay::Sar,
+        Overlay::Vwap,
+        Overlay::Rsi(14),
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            Overlay::Sma(period) => format!("SMA {}", period),
+            Overlay::Ema(period) => format!("EMA {}", period),
+            Overlay::Bollinger(period) => format!("Bollinger {}", period),
+            Overlay::Keltner(period) => format!("Keltner {}", period),
+            Overlay::Sar => "SAR".to_string(),
+            Overlay::Vwap => "VWAP".to_string(),
+            Overlay::Rsi(period) => format!("RSI {}", period),
+        }
+    }
+
+    // Dominio de los valores; los que no son `Price` se dibujan en el eje derecho
+    pub fn domain(&self) -> AxisDomain {
+        match self {
+            Overlay::Rsi(_) => AxisDomain::Fixed(0.0, 100.0),
+            _ => AxisDomain::Price,
+        }
+    }
+
+    // Valores de cada línea del indicador al cierre de cada barra. Las bandas dan tres líneas
+    // (superior, media, inferior); mientras el periodo no se completa el valor es NaN.
+    pub fn compute(&self, bars: &[Candle]) -> Vec<Vec<f64>> {
+        OverlaySeries::new(*self, bars).lines
+    }
+
+    // Barras hasta que el indicador da su primer valor
+    fn period(&self) -> usize {
+        match *self {
+            Overlay::Sma(period) | Overlay::Ema(period) | Overlay::Bollinger(period) | Overlay::Keltner(period) => period,
+            Overlay::Sar | Overlay::Vwap => 1,
+            // El RSI necesita `period` variaciones, una barra más que el periodo
+            Overlay::Rsi(period) => period + 1,
+        }
+    }
+}
+
+// Indicador de una superposición con su estado tras la última barra procesada
+#[derive(Debug, Clone)]
+enum OverlayIndicator {
+    Sma(Sma),
+    Ema(Ema),
+    Bollinger(Bollinger),
+    Keltner(Keltner),
+    Sar(ParabolicSar),
+    Vwap(Vwap),
+    Rsi(Rsi),
+}
+
+impl OverlayIndicator {
+    fn new(overlay: Overlay) -> Self {
+        match overlay {
+            Overlay::Sma(period) => OverlayIndicator::Sma(Sma::new(period)),
+            Overlay::Ema(period) => OverlayIndicator::Ema(Ema::new(period)),
+            Overlay::Bollinger(period) => OverlayIndicator::Bollinger(Bollinger::new(period)),
+            Overlay::Keltner(period) => OverlayIndicator::Keltner(Keltner::new(period)),
+            Overlay::Sar => OverlayIndicator::Sar(ParabolicSar::default()),
+            Overlay::Vwap => OverlayIndicator::Vwap(Vwap::default()),
+            Overlay::Rsi(period) => OverlayIndicator::Rsi(Rsi::new(period)),
+        }
+    }
+
+    // Actualiza con la barra y devuelve el valor de cada línea
+    fn update(&mut self, bar: &Candle) -> Vec<f64> {
+        match self {
+            OverlayIndicator::Sma(sma) => vec![update(sma, bar)],
+            OverlayIndicator::Ema(ema) => vec![update(ema, bar)],
+            OverlayIndicator::Bollinger(bollinger) => {
+                let (upper, middle, lower) = update(bollinger, bar);
+                vec![upper, middle, lower]
+            }
+            OverlayIndicator::Keltner(keltner) => {
+                let (upper, middle, lower) = update(keltner, bar);
+                vec![upper, middle, lower]
+            }
+            OverlayIndicator::Sar(sar) => vec![update(sar, bar)],
+            OverlayIndicator::Vwap(vwap) => vec![update(vwap, bar)],
+            OverlayIndicator::Rsi(rsi) => vec![update(rsi, bar)],
+        }
+    }
+}
+
+fn update<I: Indicator>(indicator: &mut I, bar: &Candle) -> I::Output {
+    indicator.update(bar);
+    indicator.value()
+}
+
+// Líneas de una superposición sobre una serie, con el estado del indicador para seguir
+// añadiendo barras sin recalcular las anteriores
+#[derive(Debug, Clone)]
+pub struct OverlaySeries {
+    overlay: Overlay,
+    indicator: OverlayIndicator,
+    // Estado antes de la última barra, por si ésta se rehace
+    before_last: OverlayIndicator,
+    pub lines: Vec<Vec<f64>>,
+}
+
+impl OverlaySeries {
+    pub fn new(overlay: Overlay, bars: &[Candle]) -> Self {
+        let indicator = OverlayIndicator::new(overlay);
+        let mut series = OverlaySeries { overlay, before_last: indicator.clone(), indicator, lines: Vec::new() };
+        series.push(bars);
+        series
+    }
+
+    // Líneas sobre `bars`, de las que sólo las `valid` primeras coinciden con las ya calculadas.
+    // Basta con seguir desde ahí si como mucho cambió la última barra procesada.
+    pub fn extend(&self, bars: &[Candle], valid: usize) -> Self {
+        let len = self.len();
+        let mut series = self.clone();
+        if valid + 1 == len {
+            series.indicator = self.before_last.clone();
+            for line in &mut series.lines {
+                line.truncate(valid);
+            }
+        } else if valid < len {
+            return OverlaySeries::new(self.overlay, bars);
+        }
+        series.push(&bars[series.len()..]);
+        series
+    }
+
+    fn len(&self) -> usize {
+        self.lines.first().map_or(0, Vec::len)
+    }
+
+    fn push(&mut self, bars: &[Candle]) {
+        let period = self.overlay.period();
+        for (i, bar) in bars.iter().enumerate() {
+            if i + 1 == bars.len() {
+                self.before_last = self.indicator.clone();
+            }
+            let index = self.len();
+            let values = self.indicator.update(bar);
+            self.lines.resize(values.len(), Vec::new());
+            for (line, value) in self.lines.iter_mut().zip(values) {
+                line.push(if index + 1 < period { f64::NAN } else { value });
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::axis::AxisDomain;
+use crate::indicadores::IndicatorValues;
+
+// Subpaneles de indicadores que se apilan bajo el gráfico de precio y comparten su ventana
+// de barras
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Volume,
+    Rsi,
+    Macd,
+    Momentum,
+}
+
+impl Pane {
+    pub const ALL: [Pane; 4] = [Pane::Volume, Pane::Rsi, Pane::Macd, Pane::Momentum];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Pane::Volume => "Volumen",
+            Pane::Rsi => "RSI (14)",
+            Pane::Macd => "MACD",
+            Pane::Momentum => "Momentum (14)",
+        }
+    }
+
+    pub fn domain(&self) -> AxisDomain {
+        match self {
+            Pane::Volume => AxisDomain::Auto,
+            Pane::Rsi => AxisDomain::Fixed(0.0, 100.0),
+            Pane::Macd | Pane::Momentum => AxisDomain::ZeroCentered,
+        }
+    }
+
+    // Niveles de referencia que se marcan en horizontal
+    pub fn guides(&self) -> &'static [f64] {
+        match self {
+            Pane::Volume => &[],
+            Pane::Rsi => &[30.0, 70.0],
+            Pane::Macd | Pane::Momentum => &[0.0],
+        }
+    }
+
+    // Líneas del indicador con su nombre; el volumen no tiene, se dibuja como barras
+    pub fn lines(&self, values: &[IndicatorValues]) -> Vec<(&'static str, Vec<f64>)> {
+        match self {
+            Pane::Volume => Vec::new(),
+            Pane::Rsi => vec![("RSI", values.iter().map(|v| v.rsi).collect())],
+            Pane::Macd => vec![
+                ("MACD", values.iter().map(|v| v.macd.0).collect()),
+                ("Señal", values.iter().map(|v| v.macd.1).collect()),
+            ],
+            Pane::Momentum => vec![("Momentum", values.iter().map(|v| v.momentum).collect())],
+        }
+    }
+}
+
+// Subpaneles visibles, de arriba abajo, y el seleccionado para quitarlo o moverlo
+#[derive(Debug, Clone)]
+pub struct PaneStack {
+    pub panes: Vec<Pane>,
+    pub selected: usize,
+}
+
+impl Default for PaneStack {
+    fn default() -> Self {
+        PaneStack {
+            panes: vec![Pane::Volume],
+            selected: 0,
+        }
+    }
+}
+
+impl PaneStack {
+    // Añade debajo el primer indicador que aún no se muestra y lo selecciona
+    pub fn add(&mut self) {
+        if let Some(pane) = Pane::ALL.into_iter().find(|p| !self.panes.contains(p)) {
+            self.panes.push(pane);
+            self.selected = self.panes.len() - 1;
+        }
+    }
+
+    pub fn remove_selected(&mut self) {
+        if self.selected < self.panes.len() {
+            self.panes.remove(self.selected);
+            self.selected = self.selected.min(self.panes.len().saturating_sub(1));
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.panes.is_empty() {
+            self.selected = (self.selected + 1) % self.panes.len();
+        }
+    }
+
+    // Sube (-1) o baja (1) el subpanel seleccionado una posición
+    pub fn move_selected(&mut self, direction: isize) {
+        let target = self.selected.checked_add_signed(direction).filter(|t| *t < self.panes.len());
+        if let Some(target) = target {
+            self.panes.swap(self.selected, target);
+            self.selected = target;
+        }
+    }
+}
@@ -1,23 +1,9 @@
-use linregress::{FormulaRegressionBuilder, RegressionDataBuilder};
-use serde::Deserialize;
-use std::borrow::Cow;
 use std::error::Error;
 
+use crate::draw::Candle;
 
-pub fn load_data_from_csv(file_path: &str) -> Result<(Vec<f64>, Vec<f64>), Box<dyn Error>> {
-    let data = crate::serde_data::load_data_from_csv(file_path)?;
-    let mut x = Vec::new();
-    let mut y = Vec::new();
-
-    for record in data {
-        x.push(record.apertura);
-        y.push(record.ultimo);
-    }
-
-    Ok((x, y))
-}
 pub fn linear_regression(
-    future_day: f64,
+    _future_day: f64,
     past_days: Vec<f64>,
     future_data: Vec<f64>,
 ) -> Result<f64, Box<dyn Error>> {
@@ -27,7 +13,6 @@ pub fn linear_regression(
 
     // Usar los últimos 30 días para la predicción
     let window_size = 30.min(past_days.len());
-    let past_days = past_days.iter().rev().take(window_size).cloned().collect::<Vec<_>>();
     let future_data = future_data.iter().rev().take(window_size).cloned().collect::<Vec<_>>();
 
     // Normalizar los días para que empiecen desde 0
@@ -40,8 +25,6 @@ pub fn linear_regression(
     let sum_xx: f64 = normalized_days.iter().map(|x| x * x).sum();
 
     let slope = (n * sum_xy - sum_x * sum_y) / (n * sum_xx - sum_x * sum_x);
-    let intercept = (sum_y - slope * sum_x) / n;
-
     // Predecir usando el último valor conocido como base
     let last_known_price = future_data[0];
     let days_into_future = 1.0; // Predecir solo un día hacia el futuro
@@ -61,7 +44,7 @@ pub fn predict_price(
     }
 }
 pub fn predict_price_moving_average(
-    future_day: f64,
+    _future_day: f64,
     past_days: Vec<f64>,
     future_data: Vec<f64>,
 ) -> Result<f64, Box<dyn Error>> {
@@ -77,31 +60,33 @@ pub fn predict_price_moving_average(
     Ok(prediction)
 }
 
-// En predicion.rs
-pub fn gcd(mut a: i64, mut b: i64) -> i64 {
-    while b != 0 {
-        let temp = b;
-        b = a % b;
-        a = temp;
+// Versiones por lotes de los indicadores, que sólo sirven de referencia a los tests de
+// indicadores.rs
+#[cfg(test)]
+pub fn calculate_sma(data: &[(String, f64)], period: usize) -> f64 {
+    // Con menos de `period` valores se devuelve el último
+    if data.len() < period {
+        return data.last().map_or(f64::NAN, |(_, v)| *v);
     }
-    a.abs()
+    data.iter().rev().take(period).map(|(_, v)| v).sum::<f64>() / period as f64
 }
 
-pub fn calculate_mcd(data: &Vec<(String, f64)>) -> i64 {
-    let prices: Vec<i64> = data.iter()
-        .map(|(_, v)| *v as i64)  // Convertimos directamente a i64 sin multiplicar por 100
-        .collect();
+// (superior, media, inferior), con la desviación típica de los últimos `period` valores
+#[cfg(test)]
+pub fn calculate_bollinger_bands(data: &[(String, f64)], period: usize) -> (f64, f64, f64) {
+    let sma = calculate_sma(data, period);
+    let variance: f64 = data.iter()
+        .rev()
+        .take(period)
+        .map(|(_, x)| (x - sma).powi(2))
+        .sum::<f64>() / period as f64;
+    let std_dev = variance.sqrt();
 
-    if prices.len() > 1 {
-        prices.windows(2)
-            .map(|w| gcd(w[0], w[1]))
-            .fold(prices[0], |acc, x| gcd(acc, x))
-    } else {
-        prices[0]
-    }
+    (sma + (2.0 * std_dev), sma, sma - (2.0 * std_dev))
 }
 
-pub fn calculate_rsi(data: &Vec<(String, f64)>, period: usize) -> f64 {
+#[cfg(test)]
+pub fn calculate_rsi(data: &[(String, f64)], period: usize) -> f64 {
     if data.len() < period + 1 {
         return 50.0; // Valor neutral si no hay suficientes datos
     }
@@ -121,9 +106,13 @@ pub fn calculate_rsi(data: &Vec<(String, f64)>, period: usize) -> f64 {
         }
     }
 
-    // Calcular promedios
-    let avg_gain: f64 = gains.iter().take(period).sum::<f64>() / period as f64;
-    let avg_loss: f64 = losses.iter().take(period).sum::<f64>() / period as f64;
+    // Calcular promedios: media simple de la primera ventana y suavizado de Wilder después
+    let mut avg_gain: f64 = gains.iter().take(period).sum::<f64>() / period as f64;
+    let mut avg_loss: f64 = losses.iter().take(period).sum::<f64>() / period as f64;
+    for (gain, loss) in gains.iter().zip(&losses).skip(period) {
+        avg_gain = (avg_gain * (period as f64 - 1.0) + gain) / period as f64;
+        avg_loss = (avg_loss * (period as f64 - 1.0) + loss) / period as f64;
+    }
 
     if avg_loss == 0.0 {
         return 100.0;
@@ -131,56 +120,120 @@ pub fn calculate_rsi(data: &Vec<(String, f64)>, period: usize) -> f64 {
 
     // Calcular RS y RSI
     let rs = avg_gain / avg_loss;
-    let rsi = 100.0 - (100.0 / (1.0 + rs));
-
-    rsi
+    100.0 - (100.0 / (1.0 + rs))
 }
-pub fn calculate_sma(data: &Vec<(String, f64)>, period: usize) -> f64 {
-    if data.len() < period {
-        return data.last().unwrap().1;
-    }
+#[cfg(test)]
+pub fn calculate_macd(data: &[(String, f64)]) -> (f64, f64) {
     let values: Vec<f64> = data.iter().map(|(_, v)| *v).collect();
-    values.iter().rev().take(period).sum::<f64>() / period as f64
-}
-pub fn calculate_macd(data: &Vec<(String, f64)>) -> (f64, f64) {
-    let ema12 = calculate_ema(data, 12);
-    let ema26 = calculate_ema(data, 26);
-    let macd_line = ema12 - ema26;
-    
-    // Calculamos el signal line usando el mismo EMA pero con los valores del MACD
-    let values_for_signal: Vec<(String, f64)> = vec![("".to_string(), macd_line)];
-    let signal_line = calculate_ema(&values_for_signal, 9);
-    
-    (macd_line, signal_line)
+    let ema12 = calculate_ema(&values, 12);
+    let ema26 = calculate_ema(&values, 26);
+
+    // La señal es la EMA de 9 periodos de la serie completa del MACD
+    let macd_values: Vec<f64> = ema12.iter().zip(&ema26).map(|(fast, slow)| fast - slow).collect();
+    let signal = calculate_ema(&macd_values, 9);
+
+    (
+        macd_values.last().copied().unwrap_or(f64::NAN),
+        signal.last().copied().unwrap_or(f64::NAN),
+    )
 }
 
-fn calculate_ema(data: &Vec<(String, f64)>, period: usize) -> f64 {
+// EMA en cada punto de la serie, partiendo del primer valor
+#[cfg(test)]
+pub fn calculate_ema(values: &[f64], period: usize) -> Vec<f64> {
     let multiplier = 2.0 / (period + 1) as f64;
-    let values: Vec<f64> = data.iter().map(|(_, v)| *v).collect();
-    let mut ema = values[0];
-    
+    let mut emas = Vec::with_capacity(values.len());
+    let mut ema = match values.first() {
+        Some(first) => *first,
+        None => return emas,
+    };
+    emas.push(ema);
+
     for value in values.iter().skip(1) {
         ema = (value - ema) * multiplier + ema;
+        emas.push(ema);
     }
-    ema
+    emas
 }
-pub fn calculate_bollinger_bands(data: &Vec<(String, f64)>, period: usize) -> (f64, f64, f64) {
-    let sma = calculate_sma(data, period);
-    let values: Vec<f64> = data.iter().map(|(_, v)| *v).collect();
-    let variance: f64 = values.iter()
-        .rev()
-        .take(period)
-        .map(|x| (x - sma).powi(2))
-        .sum::<f64>() / period as f64;
-    let std_dev = variance.sqrt();
-    
-    (sma + (2.0 * std_dev), sma, sma - (2.0 * std_dev))
-}
-pub fn calculate_momentum(data: &Vec<(String, f64)>, period: usize) -> f64 {
-    if data.len() < period {
+
+#[cfg(test)]
+pub fn calculate_momentum(data: &[(String, f64)], period: usize) -> f64 {
+    if data.len() <= period {
         return 0.0;
     }
     let current_price = data.last().unwrap().1;
     let past_price = data[data.len() - period - 1].1;
     ((current_price - past_price) / past_price) * 100.0
-}
\ No newline at end of file
+}
+#[derive(Debug, Clone)]
+pub struct VolumeProfile {
+    // (precio inferior, precio superior, volumen) de cada nivel, de menor a mayor precio
+    pub bins: Vec<(f64, f64, f64)>,
+    pub poc: usize,
+    pub value_area_low: f64,
+    pub value_area_high: f64,
+}
+
+impl VolumeProfile {
+    pub fn poc_price(&self) -> f64 {
+        let (low, high, _) = self.bins[self.poc];
+        (low + high) / 2.0
+    }
+}
+
+// Reparte el volumen de cada vela de forma uniforme entre los niveles que cubre su rango. Los
+// niveles van entre precios consecutivos de `edges`, de menor a mayor; la parte de una vela
+// que queda fuera de ellos cuenta en el nivel del extremo.
+pub fn calculate_volume_profile(candles: &[Candle], edges: &[f64], value_area_pct: f64) -> Option<VolumeProfile> {
+    let num_bins = edges.len().saturating_sub(1);
+    if candles.is_empty() || num_bins == 0 || edges[0] >= edges[num_bins] {
+        return None;
+    }
+    // Nivel que contiene un precio
+    let bin = |price: f64| edges[1..num_bins].partition_point(|edge| *edge <= price);
+
+    let mut volumes = vec![0.0; num_bins];
+    for candle in candles {
+        let first = bin(candle.low);
+        let last = bin(candle.high).max(first);
+        let share = candle.volume / (last - first + 1) as f64;
+        for volume in &mut volumes[first..=last] {
+            *volume += share;
+        }
+    }
+
+    let poc = volumes
+        .iter()
+        .enumerate()
+        .fold(0, |best, (i, v)| if *v > volumes[best] { i } else { best });
+
+    // Área de valor: se expande desde el POC hacia el lado con más volumen
+    let total: f64 = volumes.iter().sum();
+    let target = total * value_area_pct;
+    let (mut lo, mut hi) = (poc, poc);
+    let mut accumulated = volumes[poc];
+    while accumulated < target && (lo > 0 || hi < num_bins - 1) {
+        let below = if lo > 0 { volumes[lo - 1] } else { -1.0 };
+        let above = if hi < num_bins - 1 { volumes[hi + 1] } else { -1.0 };
+        if above >= below {
+            hi += 1;
+            accumulated += above;
+        } else {
+            lo -= 1;
+            accumulated += below;
+        }
+    }
+
+    let bins = volumes
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (edges[i], edges[i + 1], *v))
+        .collect();
+
+    Some(VolumeProfile {
+        bins,
+        poc,
+        value_area_low: edges[lo],
+        value_area_high: edges[hi + 1],
+    })
+}
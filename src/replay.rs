@@ -0,0 +1,159 @@
+use std::time::{Duration, Instant};
+
+use crate::draw::Candle;
+use crate::fechas::Fecha;
+use crate::workspace::Workspace;
+
+// Velocidades de reproducción, en barras por segundo
+const SPEEDS: [f64; 6] = [0.5, 1.0, 2.0, 5.0, 10.0, 20.0];
+
+// Reproducción histórica: los símbolos sólo conservan las barras hasta `cursor`, de modo que
+// gráficos, indicadores y predicciones se calculan sin ver el futuro. Las series completas
+// quedan aquí apartadas hasta que termina la reproducción.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    full: Vec<Vec<Candle>>,
+    pub cursor: Fecha,
+    pub playing: bool,
+    speed: usize,
+    last_step: Instant,
+}
+
+impl Replay {
+    // La fecha de inicio se ajusta para que todos los símbolos tengan al menos una barra
+    pub fn start(workspace: &mut Workspace, start: Fecha) -> Option<Self> {
+        let first_common = workspace
+            .symbols
+            .iter()
+            .map(|s| s.daily_bars.iter().find_map(|c| Fecha::parse(&c.date)))
+            .collect::<Option<Vec<Fecha>>>()?
+            .into_iter()
+            .max()?;
+        let cursor = start.max(first_common);
+
+        let mut full = Vec::new();
+        for symbol in &mut workspace.symbols {
+            full.push(symbol.daily_bars.clone());
+            let visible = visible_len(&symbol.daily_bars, cursor);
+            symbol.truncate_daily(visible);
+        }
+
+        Some(Replay {
+            full,
+            cursor,
+            playing: false,
+            speed: 1,
+            last_step: Instant::now(),
+        })
+    }
+
+    // Fecha de inicio por defecto: `bars` barras antes del final del símbolo seleccionado
+    pub fn default_start(workspace: &Workspace, bars: usize) -> Option<Fecha> {
+        let daily = &workspace.current().daily_bars;
+        let index = daily.len().saturating_sub(bars + 1);
+        daily.get(index).and_then(|c| Fecha::parse(&c.date))
+    }
+
+    // Muestra la siguiente fecha con datos en cualquiera de los símbolos. Devuelve false al final.
+    pub fn step_forward(&mut self, workspace: &mut Workspace) -> bool {
+        let next = workspace
+            .symbols
+            .iter()
+            .zip(&self.full)
+            .filter_map(|(symbol, full)| full.get(symbol.daily_bars.len()))
+            .filter_map(|c| Fecha::parse(&c.date))
+            .min();
+        let next = match next {
+            Some(next) => next,
+            None => {
+                self.playing = false;
+                return false;
+            }
+        };
+
+        for (symbol, full) in workspace.symbols.iter_mut().zip(&self.full) {
+            let shown = symbol.daily_bars.len();
+            let end = visible_len(full, next);
+            if end > shown {
+                symbol.append_bars(&full[shown..end]);
+            }
+        }
+        self.cursor = next;
+        true
+    }
+
+    // Vuelve a la fecha visible anterior, siempre que todos los símbolos conserven alguna barra
+    pub fn step_back(&mut self, workspace: &mut Workspace) -> bool {
+        let previous = workspace
+            .symbols
+            .iter()
+            .flat_map(|s| s.daily_bars.iter().rev().find_map(|c| Fecha::parse(&c.date).filter(|f| *f < self.cursor)))
+            .max();
+        let previous = match previous {
+            Some(previous) => previous,
+            None => return false,
+        };
+
+        let lengths: Vec<usize> = workspace
+            .symbols
+            .iter()
+            .map(|s| visible_len(&s.daily_bars, previous))
+            .collect();
+        if lengths.contains(&0) {
+            return false;
+        }
+        for (symbol, len) in workspace.symbols.iter_mut().zip(lengths) {
+            if len < symbol.daily_bars.len() {
+                symbol.truncate_daily(len);
+            }
+        }
+        self.cursor = previous;
+        true
+    }
+
+    pub fn toggle_play(&mut self) {
+        self.playing = !self.playing;
+        self.last_step = Instant::now();
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    pub fn speed(&self) -> f64 {
+        SPEEDS[self.speed]
+    }
+
+    // Avanza las barras que correspondan al tiempo transcurrido. Devuelve true si mostró alguna.
+    pub fn tick(&mut self, workspace: &mut Workspace) -> bool {
+        if !self.playing {
+            return false;
+        }
+        let interval = Duration::from_secs_f64(1.0 / self.speed());
+        let mut stepped = false;
+        while self.playing && self.last_step.elapsed() >= interval {
+            self.last_step += interval;
+            stepped |= self.step_forward(workspace);
+        }
+        stepped
+    }
+
+    // Termina la reproducción devolviendo a cada símbolo su serie completa
+    pub fn stop(self, workspace: &mut Workspace) {
+        for (symbol, full) in workspace.symbols.iter_mut().zip(&self.full) {
+            let shown = symbol.daily_bars.len();
+            if full.len() > shown {
+                symbol.append_bars(&full[shown..]);
+            }
+        }
+    }
+}
+
+// Número de barras con fecha igual o anterior a `cursor` en una serie ordenada
+fn visible_len(bars: &[Candle], cursor: Fecha) -> usize {
+    bars.partition_point(|c| Fecha::parse(&c.date).is_none_or(|f| f <= cursor))
+}
@@ -0,0 +1,86 @@
+use crate::draw::Candle;
+use crate::fechas::Fecha;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timeframe {
+    Daily,
+    Days(u32),
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl Timeframe {
+    // Siguiente temporalidad del ciclo; tras la diaria viene la de `ndays` días
+    pub fn next(&self, ndays: u32) -> Self {
+        match self {
+            Timeframe::Daily => Timeframe::Days(ndays),
+            Timeframe::Days(_) => Timeframe::Weekly,
+            Timeframe::Weekly => Timeframe::Monthly,
+            Timeframe::Monthly => Timeframe::Quarterly,
+            Timeframe::Quarterly => Timeframe::Yearly,
+            Timeframe::Yearly => Timeframe::Daily,
+        }
+    }
+
+    pub fn as_str(&self) -> String {
+        match self {
+            Timeframe::Daily => "Diario".to_string(),
+            Timeframe::Days(n) => format!("{} días", n),
+            Timeframe::Weekly => "Semanal".to_string(),
+            Timeframe::Monthly => "Mensual".to_string(),
+            Timeframe::Quarterly => "Trimestral".to_string(),
+            Timeframe::Yearly => "Anual".to_string(),
+        }
+    }
+
+    // Clave del periodo al que pertenece una fecha. Los bloques de N días se cuentan desde
+    // el 01.01.1970 para que no dependan de la primera barra cargada.
+    pub fn period_key(&self, fecha: &Fecha) -> i64 {
+        match self {
+            Timeframe::Daily => fecha.days(),
+            Timeframe::Days(n) => fecha.days().div_euclid((*n).max(1) as i64),
+            // Semanas ISO, de lunes a domingo
+            Timeframe::Weekly => fecha.days() - fecha.weekday() as i64,
+            Timeframe::Monthly => fecha.year as i64 * 12 + fecha.month as i64 - 1,
+            Timeframe::Quarterly => fecha.year as i64 * 4 + (fecha.month as i64 - 1) / 3,
+            Timeframe::Yearly => fecha.year as i64,
+        }
+    }
+}
+
+// Agrupa velas diarias (ordenadas de más antigua a más reciente) por periodo de calendario.
+// Las velas con fecha ilegible se descartan.
+pub fn resample(bars: &[Candle], timeframe: Timeframe) -> Vec<Candle> {
+    if timeframe == Timeframe::Daily {
+        return bars.iter().filter(|bar| Fecha::parse(&bar.date).is_some()).cloned().collect();
+    }
+
+    let mut candles: Vec<Candle> = Vec::new();
+    let mut current_key = None;
+
+    for bar in bars {
+        let fecha = match Fecha::parse(&bar.date) {
+            Some(fecha) => fecha,
+            None => continue,
+        };
+        let key = timeframe.period_key(&fecha);
+
+        match candles.last_mut() {
+            Some(candle) if current_key == Some(key) => {
+                candle.high = candle.high.max(bar.high);
+                candle.low = candle.low.min(bar.low);
+                candle.close = bar.close;
+                candle.volume += bar.volume;
+                candle.gap = candle.gap && bar.gap;
+            }
+            _ => {
+                candles.push(bar.clone());
+                current_key = Some(key);
+            }
+        }
+    }
+
+    candles
+}
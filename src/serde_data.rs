@@ -1,48 +1,556 @@
 use serde::Deserialize;
-use std::fs::File;
-use std::io::{self, BufReader};
-use std::str::FromStr;
+use serde_json::{Map, Value};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
 
-#[derive(Debug, Deserialize)]
+use crate::fechas::Fecha;
+
+// Fila normalizada, independiente del formato del fichero de origen
+#[derive(Debug, Clone)]
 pub struct Data {
+    // Siempre en formato "dd.mm.yyyy"
     pub fecha: String,
-    #[serde(rename = "ultimo", deserialize_with = "deserialize_floats")]
     pub ultimo: f64,
-    #[serde(rename = "apertura", deserialize_with = "deserialize_floats")]
     pub apertura: f64,
-    #[serde(rename = "maximo", deserialize_with = "deserialize_floats")]
     pub maximo: f64,
-    #[serde(rename = "minimo", deserialize_with = "deserialize_floats")]
     pub minimo: f64,
-    #[serde(rename = "vol.")]
-    pub volumen: String,
-    #[serde(rename = "% var.")]
-    pub var: String,
+    pub volumen: f64,
+    // Variación porcentual informada por la fuente, si la trae
+    pub var: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateFormat {
+    // 17.02.2025
+    Dmy,
+    // 02/17/2025
+    Mdy,
+    // 2025-02-17
+    Ymd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberFormat {
+    // 96.184,6
+    Comma,
+    // 96,184.6
+    Dot,
+}
+
+// Qué columna del fichero corresponde a cada campo y cómo leer sus valores
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColumnMapping {
+    #[serde(default = "default_mapping_name")]
+    pub name: String,
+    pub date: String,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    #[serde(default)]
+    pub volume: Option<String>,
+    #[serde(default)]
+    pub change: Option<String>,
+    pub date_format: DateFormat,
+    pub number_format: NumberFormat,
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
 }
 
-// Función para deserializar flotantes con formato de coma
-fn deserialize_floats<'de, D>(deserializer: D) -> Result<f64, D::Error>
-where
-    D: serde::de::Deserializer<'de>,
-{
-    let value: String = Deserialize::deserialize(deserializer)?;
-    value
-        .replace(',', "")
-        .parse()
-        .map_err(serde::de::Error::custom)
+fn default_mapping_name() -> String {
+    "personalizado".to_string()
 }
 
-pub fn load_data_from_csv(file_path: &str) -> Result<Vec<Data>, Box<dyn std::error::Error>> {
+fn default_delimiter() -> char {
+    ','
+}
+
+impl ColumnMapping {
+    fn preset(
+        name: &str,
+        columns: [&str; 5],
+        volume: Option<&str>,
+        change: Option<&str>,
+        date_format: DateFormat,
+        number_format: NumberFormat,
+    ) -> Self {
+        let [date, open, high, low, close] = columns;
+        ColumnMapping {
+            name: name.to_string(),
+            date: date.to_string(),
+            open: open.to_string(),
+            high: high.to_string(),
+            low: low.to_string(),
+            close: close.to_string(),
+            volume: volume.map(str::to_string),
+            change: change.map(str::to_string),
+            date_format,
+            number_format,
+            delimiter: ',',
+        }
+    }
+
+    // Exportación de investing.com en español
+    pub fn investing_es() -> Self {
+        Self::preset(
+            "investing-es",
+            ["fecha", "apertura", "maximo", "minimo", "ultimo"],
+            Some("vol."),
+            Some("% var."),
+            DateFormat::Dmy,
+            NumberFormat::Comma,
+        )
+    }
+
+    // Exportación de investing.com en inglés
+    pub fn investing_en() -> Self {
+        Self::preset(
+            "investing-en",
+            ["Date", "Open", "High", "Low", "Price"],
+            Some("Vol."),
+            Some("Change %"),
+            DateFormat::Mdy,
+            NumberFormat::Dot,
+        )
+    }
+
+    pub fn yahoo() -> Self {
+        Self::preset(
+            "yahoo",
+            ["Date", "Open", "High", "Low", "Close"],
+            Some("Volume"),
+            None,
+            DateFormat::Ymd,
+            NumberFormat::Dot,
+        )
+    }
+
+    // date,open,high,low,close,volume
+    pub fn generic() -> Self {
+        Self::preset(
+            "generic",
+            ["date", "open", "high", "low", "close"],
+            Some("volume"),
+            None,
+            DateFormat::Ymd,
+            NumberFormat::Dot,
+        )
+    }
+
+    pub fn presets() -> Vec<Self> {
+        vec![Self::investing_es(), Self::investing_en(), Self::yahoo(), Self::generic()]
+    }
+
+    // Acepta el nombre de un preset o la ruta de un JSON con un mapeo propio
+    pub fn from_name_or_file(value: &str) -> Result<Self, Box<dyn Error>> {
+        if let Some(preset) = Self::presets().into_iter().find(|p| p.name == value) {
+            return Ok(preset);
+        }
+        let content = fs::read_to_string(value)
+            .map_err(|e| format!("Mapeo de columnas desconocido {}: {}", value, e))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    // Primer preset cuyas columnas obligatorias aparecen en la cabecera
+    pub fn detect(headers: &[String]) -> Option<Self> {
+        Self::presets().into_iter().find(|p| {
+            [&p.date, &p.open, &p.high, &p.low, &p.close]
+                .iter()
+                .all(|column| find_column(headers, column).is_some())
+        })
+    }
+}
+
+fn find_column(headers: &[String], name: &str) -> Option<usize> {
+    headers.iter().position(|h| h.trim().eq_ignore_ascii_case(name.trim()))
+}
+
+// Interpreta números con separador de miles, sufijos K/M/B y signo de porcentaje
+pub fn parse_number(value: &str, format: NumberFormat) -> Option<f64> {
+    let value = value.trim().trim_end_matches('%');
+    let (number, multiplier) = match value.chars().last() {
+        Some('K') | Some('k') => (&value[..value.len() - 1], 1_000.0),
+        Some('M') | Some('m') => (&value[..value.len() - 1], 1_000_000.0),
+        Some('B') | Some('b') => (&value[..value.len() - 1], 1_000_000_000.0),
+        _ => (value, 1.0),
+    };
+    let normalized = match format {
+        NumberFormat::Comma => number.replace('.', "").replace(',', "."),
+        NumberFormat::Dot => number.replace(',', ""),
+    };
+    normalized.parse::<f64>().ok().map(|v| v * multiplier)
+}
+
+pub fn parse_date(value: &str, format: DateFormat) -> Option<Fecha> {
+    let parts: Vec<&str> = value
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|p| !p.is_empty())
+        .take(3)
+        .collect();
+    if parts.len() < 3 {
+        return None;
+    }
+    let (y, m, d) = match format {
+        DateFormat::Dmy => (parts[2], parts[1], parts[0]),
+        DateFormat::Mdy => (parts[2], parts[0], parts[1]),
+        DateFormat::Ymd => (parts[0], parts[1], parts[2]),
+    };
+    Fecha::new(y.parse().ok()?, m.parse().ok()?, d.parse().ok()?)
+}
+
+// El lector CSV separa por un solo byte, así que el delimitador tiene que ser ASCII
+fn csv_delimiter(delimiter: char) -> Result<u8, Box<dyn Error>> {
+    if !delimiter.is_ascii() {
+        return Err(format!("El delimitador {:?} no es un carácter ASCII", delimiter).into());
+    }
+    Ok(delimiter as u8)
+}
+
+// Filas de un CSV ya leído; `file_path` sólo sirve para los mensajes de error
+fn parse_csv(content: &str, file_path: &str, mapping: Option<&ColumnMapping>) -> Result<Vec<Data>, Box<dyn Error>> {
+    let delimiter = mapping.map_or(',', |m| m.delimiter);
     let mut rdr = csv::ReaderBuilder::new()
         .has_headers(true)
-        .from_path(file_path)?;
-    let mut data = Vec::new();
+        .delimiter(csv_delimiter(delimiter)?)
+        .from_reader(content.as_bytes());
 
-    for result in rdr.deserialize() {
-        let record: Data = result?;
-        data.push(record);
+    let headers: Vec<String> = rdr.headers()?.iter().map(str::to_string).collect();
+    let columns = CsvColumns::resolve(&headers, mapping, file_path)?;
+
+    let mut data = Vec::new();
+    for result in rdr.records() {
+        data.push(columns.parse(&result?));
     }
 
     Ok(data)
 }
 
+// Posición de cada campo en las filas de un CSV, resuelta a partir de su cabecera
+#[derive(Debug, Clone)]
+pub struct CsvColumns {
+    mapping: ColumnMapping,
+    date: usize,
+    open: usize,
+    high: usize,
+    low: usize,
+    close: usize,
+    volume: Option<usize>,
+    change: Option<usize>,
+}
+
+impl CsvColumns {
+    // Sin mapeo explícito se detecta por la cabecera; `source` sólo se usa en los mensajes de error
+    pub fn resolve(
+        headers: &[String],
+        mapping: Option<&ColumnMapping>,
+        source: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mapping = match mapping {
+            Some(mapping) => mapping.clone(),
+            None => ColumnMapping::detect(headers)
+                .ok_or(format!("No se reconocen las columnas de {}: {:?}", source, headers))?,
+        };
+
+        let column = |name: &str| {
+            find_column(headers, name).ok_or(format!("Falta la columna \"{}\" en {}", name, source))
+        };
+        Ok(CsvColumns {
+            date: column(&mapping.date)?,
+            open: column(&mapping.open)?,
+            high: column(&mapping.high)?,
+            low: column(&mapping.low)?,
+            close: column(&mapping.close)?,
+            volume: mapping.volume.as_deref().map(column).transpose()?,
+            change: mapping.change.as_deref().map(column).transpose()?,
+            mapping,
+        })
+    }
+
+    pub fn delimiter(&self) -> char {
+        self.mapping.delimiter
+    }
+
+    pub fn parse(&self, record: &csv::StringRecord) -> Data {
+        let format = self.mapping.number_format;
+        let field = |col: usize| record.get(col).unwrap_or("");
+        // Los precios ilegibles ("null" en Yahoo) quedan como NaN para que la validación los informe
+        let price = |col: usize| parse_number(field(col), format).unwrap_or(f64::NAN);
+
+        // Las fechas ilegibles se conservan tal cual para que la validación las informe
+        let fecha = parse_date(field(self.date), self.mapping.date_format)
+            .map(|f| f.to_string())
+            .unwrap_or_else(|| field(self.date).to_string());
+
+        Data {
+            fecha,
+            ultimo: price(self.close),
+            apertura: price(self.open),
+            maximo: price(self.high),
+            minimo: price(self.low),
+            volumen: self
+                .volume
+                .and_then(|col| parse_number(field(col), format))
+                .unwrap_or(0.0),
+            var: self.change.and_then(|col| parse_number(field(col), format)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputFormat {
+    Csv,
+    Json,
+    Ndjson,
+}
+
+impl InputFormat {
+    // Por extensión y, si no es concluyente, por el primer carácter del contenido
+    pub fn detect(file_path: &str, content: &str) -> Self {
+        let extension = Path::new(file_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        match extension.as_deref() {
+            Some("csv") => return InputFormat::Csv,
+            Some("ndjson") | Some("jsonl") => return InputFormat::Ndjson,
+            Some("json") => return InputFormat::Json,
+            _ => {}
+        }
+
+        let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+        if trimmed.starts_with('[') {
+            InputFormat::Json
+        } else if trimmed.starts_with('{') {
+            // Un único documento JSON o un objeto por línea
+            if serde_json::from_str::<Value>(trimmed).is_ok() {
+                InputFormat::Json
+            } else {
+                InputFormat::Ndjson
+            }
+        } else {
+            InputFormat::Csv
+        }
+    }
+}
+
+// Punto de entrada para cualquier formato soportado
+pub fn load_data(file_path: &str, mapping: Option<&ColumnMapping>) -> Result<Vec<Data>, Box<dyn Error>> {
+    let content = fs::read_to_string(file_path)?;
+    match InputFormat::detect(file_path, &content) {
+        InputFormat::Csv => parse_csv(&content, file_path, mapping),
+        InputFormat::Json => {
+            let value: Value = serde_json::from_str(content.trim_start_matches('\u{feff}'))?;
+            parse_json_rows(&value, mapping)
+        }
+        InputFormat::Ndjson => {
+            let rows = content
+                .lines()
+                .map(|line| line.trim_start_matches('\u{feff}').trim())
+                .filter(|line| !line.is_empty())
+                .map(serde_json::from_str)
+                .collect::<Result<Vec<Value>, _>>()?;
+            parse_json_rows(&Value::Array(rows), mapping)
+        }
+    }
+}
+
+// Interpreta barras que llegan de una en una (entrada estándar o socket): objetos JSON,
+// klines o filas CSV precedidas de su cabecera
+#[derive(Debug, Clone)]
+pub struct LineParser {
+    mapping: Option<ColumnMapping>,
+    columns: Option<CsvColumns>,
+}
+
+impl LineParser {
+    pub fn new(mapping: Option<ColumnMapping>) -> Self {
+        LineParser { mapping, columns: None }
+    }
+
+    // Devuelve None para las líneas vacías y para la cabecera CSV
+    pub fn parse_line(&mut self, line: &str) -> Result<Option<Data>, Box<dyn Error>> {
+        let line = line.trim_start_matches('\u{feff}').trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+
+        if line.starts_with('{') || line.starts_with('[') {
+            return match serde_json::from_str(line)? {
+                Value::Array(kline) => data_from_kline(&kline, KlineLayout::Standard).map(Some),
+                Value::Object(object) => {
+                    let mapping = match &self.mapping {
+                        Some(mapping) => mapping.clone(),
+                        None => {
+                            let keys: Vec<String> = object.keys().cloned().collect();
+                            ColumnMapping::detect(&keys)
+                                .ok_or(format!("No se reconocen los campos del JSON: {:?}", keys))?
+                        }
+                    };
+                    Ok(Some(data_from_object(&object, &mapping)))
+                }
+                other => Err(format!("Barra JSON no soportada: {}", other).into()),
+            };
+        }
+
+        let delimiter = match (&self.columns, &self.mapping) {
+            (Some(columns), _) => columns.delimiter(),
+            (None, Some(mapping)) => mapping.delimiter,
+            (None, None) => ',',
+        };
+        let record = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(csv_delimiter(delimiter)?)
+            .from_reader(line.as_bytes())
+            .records()
+            .next()
+            .ok_or("Línea CSV vacía")??;
+
+        match &self.columns {
+            Some(columns) => Ok(Some(columns.parse(&record))),
+            None => {
+                let headers: Vec<String> = record.iter().map(str::to_string).collect();
+                self.columns = Some(CsvColumns::resolve(&headers, self.mapping.as_ref(), "la cabecera recibida")?);
+                Ok(None)
+            }
+        }
+    }
+}
+
+// Acepta un array de objetos, un array de klines o un objeto que envuelve cualquiera de los dos
+// (`{"data": [...]}`, `{"result": {"XBTUSD": [...]}}`)
+fn parse_json_rows(value: &Value, mapping: Option<&ColumnMapping>) -> Result<Vec<Data>, Box<dyn Error>> {
+    let rows = find_rows(value).ok_or("No se encontró ningún array de barras en el JSON")?;
+    let layout = KlineLayout::detect(value);
+
+    match rows.first() {
+        None => Ok(Vec::new()),
+        Some(Value::Array(_)) => rows
+            .iter()
+            .map(|row| match row {
+                Value::Array(kline) => data_from_kline(kline, layout),
+                _ => Err("Mezcla de klines y objetos en el JSON".into()),
+            })
+            .collect(),
+        Some(Value::Object(first)) => {
+            let keys: Vec<String> = first.keys().cloned().collect();
+            let mapping = match mapping {
+                Some(mapping) => mapping.clone(),
+                None => ColumnMapping::detect(&keys)
+                    .ok_or(format!("No se reconocen los campos del JSON: {:?}", keys))?,
+            };
+            rows.iter()
+                .map(|row| match row {
+                    Value::Object(object) => Ok(data_from_object(object, &mapping)),
+                    _ => Err("Mezcla de objetos y klines en el JSON".into()),
+                })
+                .collect()
+        }
+        Some(other) => Err(format!("Barra JSON no soportada: {}", other).into()),
+    }
+}
+
+fn find_rows(value: &Value) -> Option<&Vec<Value>> {
+    match value {
+        Value::Array(rows) => Some(rows),
+        Value::Object(object) => ["data", "result", "klines", "candles", "bars"]
+            .iter()
+            .filter_map(|key| object.get(*key))
+            .chain(object.values())
+            .find_map(|v| match v {
+                Value::Array(_) | Value::Object(_) => find_rows(v),
+                _ => None,
+            }),
+        _ => None,
+    }
+}
+
+fn data_from_object(object: &Map<String, Value>, mapping: &ColumnMapping) -> Data {
+    let field = |name: &str| {
+        object
+            .iter()
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case(name.trim()))
+            .map(|(_, v)| v)
+    };
+    let number = |name: &str| field(name).and_then(|v| json_number(v, mapping.number_format));
+    let price = |name: &str| number(name).unwrap_or(f64::NAN);
+
+    let fecha = match field(&mapping.date) {
+        Some(Value::String(text)) => parse_date(text, mapping.date_format)
+            .map(|f| f.to_string())
+            .unwrap_or_else(|| text.clone()),
+        Some(Value::Number(ts)) => ts.as_f64().map(timestamp_to_fecha).unwrap_or_default(),
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+
+    Data {
+        fecha,
+        ultimo: price(&mapping.close),
+        apertura: price(&mapping.open),
+        maximo: price(&mapping.high),
+        minimo: price(&mapping.low),
+        volumen: mapping.volume.as_deref().and_then(number).unwrap_or(0.0),
+        var: mapping.change.as_deref().and_then(number),
+    }
+}
+
+// Orden de los campos de una kline
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum KlineLayout {
+    // [tiempo de apertura, open, high, low, close, volumen, ...], como Binance
+    Standard,
+    // [tiempo, open, high, low, close, vwap, volumen, operaciones], dentro de
+    // `{"result": {"PAR": [...]}}`
+    Kraken,
+}
+
+impl KlineLayout {
+    fn detect(value: &Value) -> Self {
+        match value.get("result") {
+            Some(Value::Object(_)) => KlineLayout::Kraken,
+            _ => KlineLayout::Standard,
+        }
+    }
+
+    fn volume_index(&self) -> usize {
+        match self {
+            KlineLayout::Standard => 5,
+            KlineLayout::Kraken => 6,
+        }
+    }
+}
+
+fn data_from_kline(kline: &[Value], layout: KlineLayout) -> Result<Data, Box<dyn Error>> {
+    if kline.len() < 5 {
+        return Err(format!("Kline con menos de 5 campos: {:?}", kline).into());
+    }
+    let number = |i: usize| kline.get(i).and_then(|v| json_number(v, NumberFormat::Dot));
+    let timestamp = number(0).ok_or(format!("Kline sin marca de tiempo: {:?}", kline))?;
+
+    Ok(Data {
+        fecha: timestamp_to_fecha(timestamp),
+        apertura: number(1).unwrap_or(f64::NAN),
+        maximo: number(2).unwrap_or(f64::NAN),
+        minimo: number(3).unwrap_or(f64::NAN),
+        ultimo: number(4).unwrap_or(f64::NAN),
+        volumen: number(layout.volume_index()).unwrap_or(0.0),
+        var: None,
+    })
+}
+
+fn json_number(value: &Value, format: NumberFormat) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => parse_number(s, format),
+        _ => None,
+    }
+}
+
+// Marca de tiempo Unix en segundos o milisegundos
+fn timestamp_to_fecha(timestamp: f64) -> String {
+    let seconds = if timestamp.abs() >= 1e11 { timestamp / 1000.0 } else { timestamp };
+    Fecha::from_days((seconds / 86_400.0).floor() as i64).to_string()
+}
@@ -0,0 +1,119 @@
+use crate::draw::Candle;
+use crate::indicadores::IndicatorValues;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Side::Buy => "Compra",
+            Side::Sell => "Venta",
+        }
+    }
+
+    pub fn marker(&self) -> &str {
+        match self {
+            Side::Buy => "▲",
+            Side::Sell => "▼",
+        }
+    }
+}
+
+// Reglas que convierten los indicadores del panel en señales. Cada una dispara en la barra en
+// que se produce el cruce, no mientras dura la situación.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalRule {
+    // El MACD cruza su línea de señal: al alza compra, a la baja venta
+    MacdCross,
+    // El RSI (14) sale de sobreventa (sube de 30) para comprar o de sobrecompra (baja de 70) para vender
+    RsiExit,
+    // El cierre rompe la banda superior de Bollinger (20) para comprar o la inferior para vender
+    BollingerBreakout,
+}
+
+impl SignalRule {
+    pub const ALL: [SignalRule; 3] = [SignalRule::MacdCross, SignalRule::RsiExit, SignalRule::BollingerBreakout];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            SignalRule::MacdCross => "MACD cruza señal",
+            SignalRule::RsiExit => "RSI sale de extremo",
+            SignalRule::BollingerBreakout => "Ruptura de Bollinger",
+        }
+    }
+
+    // Barras hasta que el indicador tiene su periodo completo; antes sus cruces no significan nada
+    fn warmup(&self) -> usize {
+        match self {
+            SignalRule::MacdCross => 26 + 9,
+            SignalRule::RsiExit => 14,
+            SignalRule::BollingerBreakout => 20,
+        }
+    }
+
+    // Señal en la barra `index` a partir de la barra anterior, si la hay
+    pub fn check(&self, bars: &[Candle], values: &[IndicatorValues], index: usize) -> Option<Side> {
+        if index < self.warmup().max(1) {
+            return None;
+        }
+        let (before, now) = (&values[index - 1], &values[index]);
+        match self {
+            SignalRule::MacdCross => {
+                let (diff_before, diff) = (before.macd.0 - before.macd.1, now.macd.0 - now.macd.1);
+                if diff_before <= 0.0 && diff > 0.0 {
+                    Some(Side::Buy)
+                } else if diff_before >= 0.0 && diff < 0.0 {
+                    Some(Side::Sell)
+                } else {
+                    None
+                }
+            }
+            SignalRule::RsiExit => {
+                if before.rsi <= 30.0 && now.rsi > 30.0 {
+                    Some(Side::Buy)
+                } else if before.rsi >= 70.0 && now.rsi < 70.0 {
+                    Some(Side::Sell)
+                } else {
+                    None
+                }
+            }
+            SignalRule::BollingerBreakout => {
+                let (prev, close) = (bars[index - 1].close, bars[index].close);
+                let ((upper_before, _, lower_before), (upper, _, lower)) = (before.bollinger, now.bollinger);
+                if prev <= upper_before && close > upper {
+                    Some(Side::Buy)
+                } else if prev >= lower_before && close < lower {
+                    Some(Side::Sell)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Signal {
+    // Índice de la barra en la serie del símbolo
+    pub index: usize,
+    pub side: Side,
+    pub rule: SignalRule,
+}
+
+// Señales de todas las reglas desde la barra `from`, en orden de barra. `values` son los
+// indicadores de cada barra, como los da `Symbol::indicator_history`.
+pub fn generate(bars: &[Candle], values: &[IndicatorValues], rules: &[SignalRule], from: usize) -> Vec<Signal> {
+    let mut signals = Vec::new();
+    for index in from..bars.len().min(values.len()) {
+        for rule in rules {
+            if let Some(side) = rule.check(bars, values, index) {
+                signals.push(Signal { index, side, rule: *rule });
+            }
+        }
+    }
+    signals
+}
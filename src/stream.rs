@@ -0,0 +1,156 @@
+use std::io::{self, BufRead, BufReader, Read};
+use std::net::TcpListener;
+#[cfg(unix)]
+use std::os::unix::net::UnixListener;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::fechas::Fecha;
+use crate::serde_data::{ColumnMapping, Data, LineParser};
+use crate::ajustes::Adjustment;
+use crate::config::Config;
+use crate::workspace::Workspace;
+
+// Origen de las barras en tiempo real
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamSource {
+    Stdin,
+    // Dirección en la que escuchar, p. ej. 127.0.0.1:9000
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(String),
+}
+
+impl StreamSource {
+    // "-" o "stdin", "tcp:127.0.0.1:9000", "unix:/tmp/barras.sock"
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "-" | "stdin" => Some(StreamSource::Stdin),
+            _ => {
+                if let Some(address) = value.strip_prefix("tcp:") {
+                    return Some(StreamSource::Tcp(address.to_string()));
+                }
+                #[cfg(unix)]
+                if let Some(path) = value.strip_prefix("unix:") {
+                    return Some(StreamSource::Unix(path.to_string()));
+                }
+                None
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum StreamEvent {
+    Bar(Data),
+    // Línea que no se pudo interpretar o fallo de conexión; la lectura continúa
+    Error(String),
+    // La entrada estándar terminó
+    Closed,
+}
+
+// Lee barras en un hilo aparte y las entrega por el canal devuelto. Los sockets aceptan
+// conexiones una detrás de otra; cada conexión puede enviar su propia cabecera CSV.
+pub fn spawn(source: StreamSource, mapping: Option<ColumnMapping>) -> io::Result<Receiver<StreamEvent>> {
+    let (tx, rx) = mpsc::channel();
+    match source {
+        StreamSource::Stdin => {
+            thread::spawn(move || {
+                read_lines(io::stdin().lock(), &mapping, &tx);
+                let _ = tx.send(StreamEvent::Closed);
+            });
+        }
+        StreamSource::Tcp(address) => {
+            let listener = TcpListener::bind(&address)?;
+            thread::spawn(move || {
+                for connection in listener.incoming() {
+                    match connection {
+                        Ok(connection) => read_lines(connection, &mapping, &tx),
+                        Err(e) => {
+                            let _ = tx.send(StreamEvent::Error(e.to_string()));
+                        }
+                    }
+                }
+            });
+        }
+        #[cfg(unix)]
+        StreamSource::Unix(path) => {
+            // Un socket que quedó de una ejecución anterior impediría el bind
+            let _ = std::fs::remove_file(&path);
+            let listener = UnixListener::bind(&path)?;
+            thread::spawn(move || {
+                for connection in listener.incoming() {
+                    match connection {
+                        Ok(connection) => read_lines(connection, &mapping, &tx),
+                        Err(e) => {
+                            let _ = tx.send(StreamEvent::Error(e.to_string()));
+                        }
+                    }
+                }
+            });
+        }
+    }
+    Ok(rx)
+}
+
+fn read_lines(reader: impl Read, mapping: &Option<ColumnMapping>, tx: &Sender<StreamEvent>) {
+    let mut parser = LineParser::new(mapping.clone());
+    for line in BufReader::new(reader).lines() {
+        let event = match line {
+            Ok(line) => match parser.parse_line(&line) {
+                Ok(Some(data)) if Fecha::parse(&data.fecha).is_some() => StreamEvent::Bar(data),
+                Ok(Some(data)) => StreamEvent::Error(format!("fecha ilegible: {}", data.fecha)),
+                Ok(None) => continue,
+                Err(e) => StreamEvent::Error(format!("línea ignorada ({})", e)),
+            },
+            Err(e) => StreamEvent::Error(e.to_string()),
+        };
+        // Si la interfaz ya se cerró no queda nadie a quien avisar
+        if tx.send(event).is_err() {
+            return;
+        }
+    }
+}
+
+// Aplica sin bloquear lo que ha llegado desde la última consulta al primer instrumento cargado,
+// con la misma validación, ajustes y remediación que los ficheros. Devuelve el aviso para el
+// panel de controles, o None si no llegó nada.
+pub fn apply_pending(
+    rx: &Receiver<StreamEvent>,
+    workspace: &mut Workspace,
+    config: &Config,
+    adjustments: &[Adjustment],
+) -> Option<String> {
+    let mut rows: Vec<Data> = Vec::new();
+    let mut messages = Vec::new();
+    for event in rx.try_iter() {
+        match event {
+            StreamEvent::Bar(data) => rows.push(data),
+            StreamEvent::Error(e) => messages.push(format!("stream: {}", e)),
+            StreamEvent::Closed => messages.push("stream: entrada cerrada".to_string()),
+        }
+    }
+
+    if let Some(last) = rows.last() {
+        let symbol = &mut workspace.symbols[0];
+        let received = format!("{}: {} barra(s) recibidas, última {} ({})", symbol.name, rows.len(), last.fecha, last.ultimo);
+        match symbol.append_data(&rows, config, adjustments) {
+            Ok((report, ignored)) => {
+                messages.insert(0, received);
+                if !report.is_clean() {
+                    messages.push(format!("{} problema(s) de calidad", report.issues.len()));
+                }
+                if ignored > 0 {
+                    messages.push(format!("{} ignoradas por ser anteriores a la última", ignored));
+                }
+            }
+            Err(e) => messages.insert(0, format!("{}, descartadas: {}", received, e)),
+        }
+    }
+
+    if messages.is_empty() {
+        None
+    } else {
+        Some(messages.join(" · "))
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::fechas::Fecha;
+use crate::serde_data::Data;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IssueKind {
+    InvalidDate,
+    DuplicateDate,
+    // Días sin dato entre esta fecha y la anterior
+    MissingDates(Vec<Fecha>),
+    HighBelowLow { high: f64, low: f64 },
+    CloseOutsideRange { close: f64, low: f64, high: f64 },
+    InvalidPrice,
+    NonPositivePrice,
+    ExtremeJump { change_pct: f64 },
+    VarMismatch { reported: f64, computed: f64 },
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub fecha: String,
+    pub kind: IssueKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct ValidationOptions {
+    // Variación diaria (en %) a partir de la cual se marca un salto extremo
+    pub max_jump_pct: f64,
+    // Diferencia admitida entre "% var." y la variación calculada, en puntos porcentuales
+    pub var_tolerance: f64,
+}
+
+impl Default for ValidationOptions {
+    fn default() -> Self {
+        ValidationOptions {
+            max_jump_pct: 25.0,
+            var_tolerance: 0.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub rows: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IssueKind::InvalidDate => write!(f, "fecha ilegible"),
+            IssueKind::DuplicateDate => write!(f, "fecha duplicada"),
+            IssueKind::MissingDates(days) if days.len() == 1 => write!(f, "falta el día {}", days[0]),
+            IssueKind::MissingDates(days) => write!(
+                f,
+                "faltan {} día(s) desde {} hasta {}",
+                days.len(),
+                days[0],
+                days[days.len() - 1]
+            ),
+            IssueKind::HighBelowLow { high, low } => {
+                write!(f, "máximo {:.2} menor que mínimo {:.2}", high, low)
+            }
+            IssueKind::CloseOutsideRange { close, low, high } => write!(
+                f,
+                "cierre {:.2} fuera del rango [{:.2}, {:.2}]",
+                close, low, high
+            ),
+            IssueKind::InvalidPrice => write!(f, "precio ilegible"),
+            IssueKind::NonPositivePrice => write!(f, "precio cero o negativo"),
+            IssueKind::ExtremeJump { change_pct } => write!(f, "salto extremo de {:.2}%", change_pct),
+            IssueKind::VarMismatch { reported, computed } => write!(
+                f,
+                "% var. {:.2}% no coincide con la variación calculada {:.2}%",
+                reported, computed
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_clean() {
+            return writeln!(f, "Validación: {} filas sin problemas", self.rows);
+        }
+        writeln!(f, "Validación: {} problema(s) en {} filas", self.issues.len(), self.rows)?;
+        for issue in &self.issues {
+            writeln!(f, "  {}: {}", issue.fecha, issue.kind)?;
+        }
+        Ok(())
+    }
+}
+
+// Revisa las filas tal como se cargaron, sin modificarlas
+pub fn validate(data: &[Data], options: &ValidationOptions) -> ValidationReport {
+    let mut issues = Vec::new();
+    let mut seen = HashSet::new();
+    let mut rows: Vec<(Fecha, &Data)> = Vec::new();
+
+    for d in data {
+        let push = |issues: &mut Vec<Issue>, kind| issues.push(Issue { fecha: d.fecha.clone(), kind });
+
+        match Fecha::parse(&d.fecha) {
+            Some(fecha) if !seen.insert(fecha) => push(&mut issues, IssueKind::DuplicateDate),
+            Some(fecha) => rows.push((fecha, d)),
+            None => push(&mut issues, IssueKind::InvalidDate),
+        }
+
+        let prices = [d.ultimo, d.apertura, d.maximo, d.minimo];
+        if prices.iter().any(|p| p.is_nan()) {
+            push(&mut issues, IssueKind::InvalidPrice);
+        } else if prices.iter().any(|p| *p <= 0.0) {
+            push(&mut issues, IssueKind::NonPositivePrice);
+        }
+        if d.maximo < d.minimo {
+            push(&mut issues, IssueKind::HighBelowLow { high: d.maximo, low: d.minimo });
+        } else if d.ultimo < d.minimo || d.ultimo > d.maximo {
+            push(&mut issues, IssueKind::CloseOutsideRange {
+                close: d.ultimo,
+                low: d.minimo,
+                high: d.maximo,
+            });
+        }
+    }
+
+    rows.sort_by_key(|(fecha, _)| *fecha);
+    let weekends = trades_weekends(rows.iter().map(|(fecha, _)| fecha));
+
+    for pair in rows.windows(2) {
+        let (prev_fecha, prev) = pair[0];
+        let (fecha, d) = pair[1];
+
+        let missing = missing_between(&prev_fecha, &fecha, weekends);
+        if !missing.is_empty() {
+            issues.push(Issue { fecha: d.fecha.clone(), kind: IssueKind::MissingDates(missing) });
+        }
+
+        if !(prev.ultimo > 0.0 && d.ultimo > 0.0) {
+            continue;
+        }
+        let change_pct = (d.ultimo / prev.ultimo - 1.0) * 100.0;
+        if change_pct.abs() > options.max_jump_pct {
+            issues.push(Issue { fecha: d.fecha.clone(), kind: IssueKind::ExtremeJump { change_pct } });
+        }
+        if let Some(reported) = d.var {
+            if (reported - change_pct).abs() > options.var_tolerance {
+                issues.push(Issue {
+                    fecha: d.fecha.clone(),
+                    kind: IssueKind::VarMismatch { reported, computed: change_pct },
+                });
+            }
+        }
+    }
+
+    ValidationReport { rows: data.len(), issues }
+}
+
+// Si la serie no trae fines de semana se asume un mercado que no cotiza en ellos
+pub fn trades_weekends<'a>(mut fechas: impl Iterator<Item = &'a Fecha>) -> bool {
+    fechas.any(|fecha| fecha.weekday() >= 5)
+}
+
+// Días hábiles sin dato estrictamente entre dos fechas
+pub fn missing_between(from: &Fecha, to: &Fecha, weekends: bool) -> Vec<Fecha> {
+    (from.days() + 1..to.days())
+        .map(Fecha::from_days)
+        .filter(|f| weekends || f.weekday() < 5)
+        .collect()
+}
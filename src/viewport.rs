@@ -0,0 +1,144 @@
+use std::ops::Range;
+
+use crate::draw::Candle;
+use crate::fechas::Fecha;
+
+// Menor número de barras que se puede mostrar al ampliar
+const MIN_SPAN: usize = 10;
+
+// Ventana de fechas visible en los gráficos. Se guarda por fechas y no por índices, de modo
+// que se conserva al cambiar de temporalidad o de símbolo. Si llega a la última barra la sigue:
+// las barras nuevas (recargas, tiempo real, replay) entran por la derecha y la ventana avanza
+// manteniendo su duración.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Viewport {
+    // Fechas de la primera y la última barra visibles; None muestra la serie completa
+    window: Option<(Fecha, Fecha)>,
+    // La ventana acaba en la última barra de la serie
+    at_end: bool,
+}
+
+impl Viewport {
+    // Índices visibles de la serie `bars`. La primera barra es la que contiene la fecha de
+    // inicio, por si la temporalidad agrupa varios días en cada barra.
+    pub fn range(&self, bars: &[Candle]) -> Range<usize> {
+        let len = bars.len();
+        let (first, last) = match (self.window, bars.last().and_then(date)) {
+            (Some((first, last)), _) if !self.at_end => (first, last),
+            (Some((first, last)), Some(end)) => (Fecha::from_days(end.days() - (last.days() - first.days())), end),
+            _ => return 0..len,
+        };
+        let start = bars.partition_point(|c| date(c).is_none_or(|f| f <= first)).saturating_sub(1);
+        let end = bars.partition_point(|c| date(c).is_none_or(|f| f <= last));
+        let start = start.min(len.saturating_sub(1));
+        start..end.max(start + 1).min(len)
+    }
+
+    // Fechas de la primera y la última barra visibles mientras hay zoom
+    pub fn window(&self, bars: &[Candle]) -> Option<(Fecha, Fecha)> {
+        self.window?;
+        let range = self.range(bars);
+        Some((date(bars.get(range.start)?)?, date(bars.get(range.end.checked_sub(1)?)?)?))
+    }
+
+    // Muestra las barras `range` de la serie; la serie completa quita el zoom
+    pub fn set_range(&mut self, bars: &[Candle], range: Range<usize>) {
+        let len = bars.len();
+        let end = range.end.min(len);
+        let start = range.start.min(end);
+        if start == 0 && end == len {
+            return self.reset();
+        }
+        let last = end.checked_sub(1).and_then(|i| bars.get(i));
+        if let (Some(first), Some(last)) = (bars.get(start).and_then(date), last.and_then(date)) {
+            self.window = Some((first, last));
+            self.at_end = end == len;
+        }
+    }
+
+    pub fn zoom_in(&mut self, bars: &[Candle]) {
+        let range = self.range(bars);
+        let span = (range.len() * 2 / 3).max(MIN_SPAN).min(bars.len());
+        self.show(bars, range.end, span);
+    }
+
+    pub fn zoom_out(&mut self, bars: &[Candle]) {
+        let range = self.range(bars);
+        let span = range.len() * 3 / 2 + 1;
+        if span >= bars.len() {
+            self.reset();
+        } else {
+            self.show(bars, range.end, span);
+        }
+    }
+
+    // Amplía o reduce dejando la barra `anchor` en la misma posición relativa `fraction` (0 a 1)
+    // de la ventana, como al usar la rueda del ratón sobre ella
+    pub fn zoom_at(&mut self, bars: &[Candle], anchor: usize, fraction: f64, zoom_in: bool) {
+        if zoom_in {
+            self.zoom_in(bars);
+        } else {
+            self.zoom_out(bars);
+        }
+        if self.window.is_none() {
+            return;
+        }
+        let span = self.range(bars).len();
+        let start = anchor.saturating_sub((fraction * span as f64) as usize);
+        self.show(bars, start + span, span);
+    }
+
+    // Barras ocultas a la derecha de la ventana
+    pub fn offset(&self, bars: &[Candle]) -> usize {
+        bars.len() - self.range(bars).end
+    }
+
+    // Coloca la ventana `offset` barras antes del final, p. ej. al arrastrar con el ratón
+    pub fn set_offset(&mut self, bars: &[Candle], offset: usize) {
+        let span = self.range(bars).len();
+        self.show(bars, bars.len().saturating_sub(offset), span);
+    }
+
+    // Desplaza la ventana una décima parte de su anchura; negativo hacia el pasado
+    pub fn pan(&mut self, bars: &[Candle], direction: isize) {
+        let range = self.range(bars);
+        let step = (range.len() / 10).max(1);
+        let end = if direction < 0 { range.end.saturating_sub(step) } else { range.end + step };
+        self.show(bars, end, range.len());
+    }
+
+    pub fn go_to_start(&mut self, bars: &[Candle]) {
+        let span = self.range(bars).len();
+        self.show(bars, span, span);
+    }
+
+    pub fn go_to_end(&mut self, bars: &[Candle]) {
+        let span = self.range(bars).len();
+        self.show(bars, bars.len(), span);
+    }
+
+    // Desplaza la ventana lo justo para que `index` quede a la vista
+    pub fn ensure_visible(&mut self, bars: &[Candle], index: usize) {
+        let range = self.range(bars);
+        if index < range.start {
+            self.show(bars, index + range.len(), range.len());
+        } else if index >= range.end {
+            self.show(bars, index + 1, range.len());
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Viewport::default();
+    }
+
+    // Muestra `span` barras que acaban en `end`, sin salirse de la serie
+    fn show(&mut self, bars: &[Candle], end: usize, span: usize) {
+        let span = span.min(bars.len());
+        let end = end.clamp(span, bars.len());
+        self.set_range(bars, end - span..end);
+    }
+}
+
+fn date(bar: &Candle) -> Option<Fecha> {
+    Fecha::parse(&bar.date)
+}
@@ -0,0 +1,43 @@
+use std::fs;
+use std::time::SystemTime;
+
+// Vigila un fichero comparando fecha de modificación y tamaño en cada consulta
+#[derive(Debug, Clone)]
+pub struct FileWatcher {
+    path: String,
+    last_seen: Option<(SystemTime, u64)>,
+    // Cambio observado que todavía no se ha estabilizado
+    pending: Option<(SystemTime, u64)>,
+}
+
+impl FileWatcher {
+    pub fn new(path: &str) -> Self {
+        FileWatcher {
+            path: path.to_string(),
+            last_seen: metadata(path),
+            pending: None,
+        }
+    }
+
+    // Devuelve true una sola vez por cambio, cuando el fichero deja de cambiar entre dos
+    // consultas seguidas, para no leer una exportación a medio escribir.
+    pub fn poll(&mut self) -> bool {
+        let current = metadata(&self.path);
+        if current.is_none() || current == self.last_seen {
+            self.pending = None;
+            return false;
+        }
+        if current == self.pending {
+            self.last_seen = current;
+            self.pending = None;
+            return true;
+        }
+        self.pending = current;
+        false
+    }
+}
+
+fn metadata(path: &str) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
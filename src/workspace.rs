@@ -0,0 +1,569 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::ajustes::{apply_adjustments, fill_bars, Adjustment};
+use crate::backtest::{backtest_signals, BacktestResult, BacktestSettings};
+use crate::cache::{IndicatorCache, IndicatorKey};
+use crate::comparacion::{compare, Comparison, ComparisonSettings};
+use crate::config::Config;
+use crate::correlacion::{correlation_matrix, rolling, CorrelationMethod, CorrelationSettings};
+use crate::draw::{Candle, ChartType};
+use crate::drawings::{is_sidecar, load_drawings, save_drawings, sidecar_path, Drawing};
+use crate::fechas::Fecha;
+use crate::indicadores::{Indicator, IndicatorSet, IndicatorValues};
+use crate::overlays::{Overlay, OverlaySeries};
+use crate::predicion;
+use crate::predicion::{predict_price, VolumeProfile};
+use crate::resample::{resample, Timeframe};
+use crate::signals::{generate, Signal, SignalRule};
+use crate::serde_data::{load_data, Data};
+use crate::validacion::{validate, ValidationOptions, ValidationReport};
+use crate::watch::FileWatcher;
+
+// Un instrumento cargado con su serie diaria, la serie remuestreada y sus cálculos
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub file_path: String,
+    pub report: ValidationReport,
+    // Fecha de la última barra leída del fichero; las posteriores llegaron en tiempo real
+    file_end: Option<Fecha>,
+    pub daily_bars: Vec<Candle>,
+    pub bars: Vec<Candle>,
+    pub chart_data: Vec<(String, f64)>,
+    // Indicadores sobre `bars`, y su estado antes de la última barra por si ésta se rehace
+    pub indicators: IndicatorSet,
+    indicators_before_last: IndicatorSet,
+    // Valores de los indicadores al cerrar cada barra de `bars`
+    history: Vec<IndicatorValues>,
+    timeframe: Timeframe,
+    // Cambia cada vez que cambian `bars`; invalida la caché de indicadores
+    version: u64,
+    cache: IndicatorCache,
+    predictions: HashMap<ChartType, f64>,
+    watcher: FileWatcher,
+    // Anotaciones del usuario, guardadas junto al fichero de datos
+    pub drawings: Vec<Drawing>,
+}
+
+// Resultado de recargar un fichero modificado
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reload {
+    // El fichero sólo añadió barras (o rehízo la última) y se extendió la serie existente
+    Appended(usize),
+    // El histórico cambió y se reconstruyó la serie completa
+    Replaced,
+}
+
+impl Symbol {
+    pub fn load(file_path: &str, config: &Config, adjustments: &[Adjustment]) -> Result<Self, Box<dyn Error>> {
+        let name = Path::new(file_path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(file_path)
+            .to_string();
+
+        let watcher = FileWatcher::new(file_path);
+        let Series { report, daily_bars, notes } = read_series(file_path, &name, config, adjustments)?;
+        eprint!("{}: {}", name, report);
+        for note in &notes {
+            eprintln!("{}: {}", name, note);
+        }
+        check_strict(&report, file_path, config)?;
+        // Unos dibujos ilegibles no impiden abrir los datos
+        let drawings = load_drawings(&sidecar_path(file_path)).unwrap_or_else(|e| {
+            eprintln!("{}: no se pudieron leer los dibujos: {}", name, e);
+            Vec::new()
+        });
+
+        let mut symbol = Symbol {
+            name,
+            file_path: file_path.to_string(),
+            report,
+            file_end: daily_bars.last().and_then(date),
+            daily_bars,
+            bars: Vec::new(),
+            chart_data: Vec::new(),
+            indicators: IndicatorSet::default(),
+            indicators_before_last: IndicatorSet::default(),
+            history: Vec::new(),
+            timeframe: Timeframe::Daily,
+            version: 0,
+            cache: IndicatorCache::default(),
+            predictions: HashMap::new(),
+            watcher,
+            drawings,
+        };
+        symbol.set_timeframe(Timeframe::Daily);
+        Ok(symbol)
+    }
+
+    // Vuelve a leer y validar el fichero. Si el histórico anterior sigue intacto sólo se
+    // remuestrea el tramo nuevo; si no, se reconstruye todo. Con --strict y problemas de
+    // calidad se conservan los datos anteriores.
+    pub fn reload(&mut self, config: &Config, adjustments: &[Adjustment]) -> Result<Reload, Box<dyn Error>> {
+        let Series { report, mut daily_bars, .. } = read_series(&self.file_path, &self.name, config, adjustments)?;
+        check_strict(&report, &self.file_path, config)?;
+        self.report = report;
+
+        // Las barras recibidas en tiempo real que el fichero aún no incluye se conservan
+        let file_end = daily_bars.last().and_then(date);
+        let streamed_after = self.file_end.max(file_end);
+        daily_bars.extend(self.daily_bars.iter().filter(|c| date(c) > streamed_after).cloned());
+        self.file_end = file_end;
+
+        // La última barra puede ser una sesión en curso que el exportador reescribe
+        let stable = self.daily_bars.len().saturating_sub(1);
+        let appended = daily_bars.len() >= self.daily_bars.len()
+            && daily_bars[..stable] == self.daily_bars[..stable];
+        if !appended {
+            self.daily_bars = daily_bars;
+            self.set_timeframe(self.timeframe);
+            return Ok(Reload::Replaced);
+        }
+
+        let added = daily_bars.len() - self.daily_bars.len();
+        self.replace_tail(stable, &daily_bars[stable..]);
+        Ok(Reload::Appended(added))
+    }
+
+    // Filas recibidas en tiempo real. Pasan por la misma validación, ajustes y remediación que las
+    // del fichero y se añaden con `append_bars`; con --strict y problemas de calidad se descartan.
+    // Devuelve el informe de validación y cuántas se ignoraron por ser anteriores a la última barra.
+    pub fn append_data(
+        &mut self,
+        data: &[Data],
+        config: &Config,
+        adjustments: &[Adjustment],
+    ) -> Result<(ValidationReport, usize), Box<dyn Error>> {
+        // De varias filas seguidas con la misma fecha (sesión en curso) sólo cuenta la última
+        let last = self.daily_bars.last().and_then(date);
+        let mut rows: Vec<Data> = Vec::new();
+        for row in data.iter().filter(|d| Fecha::parse(&d.fecha) >= last) {
+            if rows.last().is_some_and(|r| Fecha::parse(&r.fecha) == Fecha::parse(&row.fecha)) {
+                rows.pop();
+            }
+            rows.push(row.clone());
+        }
+        let ignored = data.iter().filter(|d| Fecha::parse(&d.fecha) < last).count();
+
+        let first = rows.iter().filter_map(|d| Fecha::parse(&d.fecha)).min();
+        let previous = self.daily_bars.iter().rev().find(|c| date(c) < first);
+        let Series { report, daily_bars, .. } = prepare_series(&rows, &self.name, config, adjustments, previous);
+        check_strict(&report, "las barras en tiempo real", config)?;
+        self.append_bars(&daily_bars);
+        Ok((report, ignored))
+    }
+
+    // Añade barras al final de la serie (tiempo real, replay). Una barra con la fecha de la
+    // última la sustituye (sesión en curso); las anteriores a la última se ignoran.
+    pub fn append_bars(&mut self, bars: &[Candle]) {
+        let mut from = self.daily_bars.len();
+        let mut tail: Vec<Candle> = Vec::new();
+        for bar in bars {
+            let fecha = match Fecha::parse(&bar.date) {
+                Some(fecha) => fecha,
+                None => continue,
+            };
+            let last = tail.last().or(self.daily_bars.last()).and_then(|c| Fecha::parse(&c.date));
+            if last.is_some_and(|last| fecha < last) {
+                continue;
+            }
+            if last == Some(fecha) && tail.pop().is_none() {
+                from -= 1;
+            }
+            tail.push(bar.clone());
+        }
+
+        if !tail.is_empty() {
+            self.replace_tail(from, &tail);
+        }
+    }
+
+    // Sustituye las barras diarias desde `from` y rehace sólo los periodos afectados
+    fn replace_tail(&mut self, from: usize, tail: &[Candle]) {
+        self.daily_bars.truncate(from);
+        self.daily_bars.extend_from_slice(tail);
+
+        let timeframe = self.timeframe;
+        let key = |c: &Candle| Fecha::parse(&c.date).map(|f| timeframe.period_key(&f));
+        let first_key = match self.daily_bars.get(from).and_then(key) {
+            Some(first_key) => first_key,
+            None => return self.set_timeframe(timeframe),
+        };
+
+        // El último periodo remuestreado puede haber quedado incompleto: se descarta y se
+        // vuelve a agrupar desde su primera barra diaria
+        let kept = self.bars.iter().take_while(|c| key(c) < Some(first_key)).count();
+        let daily_start = self
+            .daily_bars
+            .iter()
+            .position(|c| key(c) >= Some(first_key))
+            .unwrap_or(from);
+
+        self.bars.truncate(kept);
+        self.bars.extend(resample(&self.daily_bars[daily_start..], timeframe));
+        self.chart_data.truncate(kept);
+        self.chart_data.extend(to_chart_data(&self.bars[kept..]));
+        self.update_indicators(kept);
+        self.version += 1;
+        self.cache.truncate(self.version, kept);
+        self.predictions.clear();
+    }
+
+    // Lleva los indicadores hasta el final de `bars` partiendo del estado guardado más cercano
+    // a `from`; sólo si cambió algo anterior a la última barra se recalculan desde el principio
+    fn update_indicators(&mut self, from: usize) {
+        let mut from = from;
+        if from + 1 == self.indicators.len() {
+            self.indicators = self.indicators_before_last.clone();
+        } else if from != self.indicators.len() {
+            self.indicators = IndicatorSet::default();
+            from = 0;
+        }
+
+        self.history.truncate(from);
+        for (i, bar) in self.bars.iter().enumerate().skip(from) {
+            if i + 1 == self.bars.len() {
+                self.indicators_before_last = self.indicators.clone();
+            }
+            self.indicators.update(bar);
+            self.history.push(self.indicators.values());
+        }
+    }
+
+    // Deja sólo las `len` primeras barras diarias
+    pub fn truncate_daily(&mut self, len: usize) {
+        self.daily_bars.truncate(len);
+        self.set_timeframe(self.timeframe);
+    }
+
+    // Serie remuestreada que alimenta todos los gráficos e indicadores
+    pub fn set_timeframe(&mut self, timeframe: Timeframe) {
+        self.timeframe = timeframe;
+        self.bars = resample(&self.daily_bars, timeframe);
+        self.chart_data = to_chart_data(&self.bars);
+        self.update_indicators(0);
+        self.version += 1;
+        self.cache.clear();
+        self.predictions.clear();
+    }
+
+    pub fn prediction(&mut self, chart_type: &ChartType) -> Result<f64, Box<dyn Error>> {
+        if let Some(value) = self.predictions.get(chart_type) {
+            return Ok(*value);
+        }
+        let value = compute_prediction(chart_type, &self.bars, &self.indicators)?;
+        self.predictions.insert(chart_type.clone(), value);
+        Ok(value)
+    }
+
+    // Añade un dibujo y reescribe el fichero de dibujos del símbolo
+    pub fn add_drawing(&mut self, drawing: Drawing) -> Result<(), Box<dyn Error>> {
+        self.drawings.push(drawing);
+        save_drawings(&sidecar_path(&self.file_path), &self.drawings)
+    }
+
+    pub fn remove_last_drawing(&mut self) -> Result<Option<Drawing>, Box<dyn Error>> {
+        let removed = self.drawings.pop();
+        save_drawings(&sidecar_path(&self.file_path), &self.drawings)?;
+        Ok(removed)
+    }
+
+    // Indicadores tal como estaban al cerrar cada barra, para consultar cualquier punto de la serie
+    pub fn indicator_history(&self) -> &[IndicatorValues] {
+        &self.history
+    }
+
+    // Señales de todas las reglas sobre la serie, en orden de barra. Si sólo cambió el final
+    // de la serie se conservan las de las barras anteriores.
+    pub fn signals(&self) -> Rc<Vec<Signal>> {
+        self.cache.get_or_update::<Rc<Vec<Signal>>>(IndicatorKey::Signals, self.version, |previous| {
+            let (mut signals, from) = match previous {
+                Some((signals, valid)) => (signals.iter().copied().filter(|s| s.index < valid).collect(), valid),
+                None => (Vec::new(), 0),
+            };
+            signals.extend(generate(&self.bars, &self.history, &SignalRule::ALL, from));
+            Rc::new(signals)
+        })
+    }
+
+    // Líneas de una superposición en las barras `range`. El VWAP se ancla a la primera barra
+    // visible; el resto se calcula sobre la serie completa, se extiende con las barras nuevas
+    // y se recorta.
+    pub fn overlay(&self, overlay: Overlay, range: Range<usize>) -> Vec<Vec<f64>> {
+        if overlay == Overlay::Vwap {
+            return overlay.compute(&self.bars[range]);
+        }
+        let key = IndicatorKey::Overlay(overlay);
+        let series = self.cache.get_or_update::<Rc<OverlaySeries>>(key, self.version, |previous| {
+            Rc::new(match previous {
+                Some((series, valid)) => series.extend(&self.bars, valid),
+                None => OverlaySeries::new(overlay, &self.bars),
+            })
+        });
+        series.lines.iter().map(|line| line[range.clone()].to_vec()).collect()
+    }
+
+    // Perfil de volumen de las barras visibles, con los niveles entre precios consecutivos de `edges`
+    pub fn volume_profile(&self, range: Range<usize>, edges: Vec<f64>, value_area_pct: u32) -> Option<VolumeProfile> {
+        let params = (range, edges, value_area_pct);
+        self.cache.get_or_replace(IndicatorKey::VisibleProfile, self.version, params, |(range, edges, pct)| {
+            predicion::calculate_volume_profile(&self.bars[range.clone()], edges, *pct as f64 / 100.0)
+        })
+    }
+
+    // Backtest de la estrategia de señales; se repite sólo si cambian los datos o los ajustes
+    pub fn backtest(&self, settings: &BacktestSettings) -> Rc<BacktestResult> {
+        self.cache.get_or_replace(IndicatorKey::Backtest, self.version, *settings, |settings| {
+            Rc::new(backtest_signals(self, settings))
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub symbols: Vec<Symbol>,
+    pub selected: usize,
+    // Cálculos entre símbolos, guardados entre fotogramas como los indicadores de cada símbolo
+    cache: IndicatorCache,
+}
+
+impl Workspace {
+    pub fn load(config: &Config, adjustments: &[Adjustment]) -> Result<Self, Box<dyn Error>> {
+        let mut symbols = Vec::new();
+        for file_path in expand_paths(&config.file_paths)? {
+            symbols.push(Symbol::load(&file_path, config, adjustments)?);
+        }
+        if symbols.is_empty() {
+            return Err("No se encontró ningún fichero de datos.".into());
+        }
+        Ok(Workspace { symbols, selected: 0, cache: IndicatorCache::default() })
+    }
+
+    pub fn current(&self) -> &Symbol {
+        &self.symbols[self.selected]
+    }
+
+    pub fn current_mut(&mut self) -> &mut Symbol {
+        &mut self.symbols[self.selected]
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.symbols.len();
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = (self.selected + self.symbols.len() - 1) % self.symbols.len();
+    }
+
+    pub fn set_timeframe(&mut self, timeframe: Timeframe) {
+        for symbol in &mut self.symbols {
+            symbol.set_timeframe(timeframe);
+        }
+    }
+
+    // Versión de los datos del conjunto: las de los símbolos sólo crecen, así que su suma cambia
+    // en cuanto cambian los datos de cualquiera
+    fn version(&self) -> u64 {
+        self.symbols.iter().map(|symbol| symbol.version).sum()
+    }
+
+    // Series normalizadas de todos los símbolos; se rehacen sólo si cambian los datos o los ajustes
+    pub fn comparison(&self, settings: &ComparisonSettings) -> Rc<Option<Comparison>> {
+        self.cache.get_or_replace(IndicatorKey::Comparison, self.version(), settings.clone(), |settings| {
+            Rc::new(compare(self, settings))
+        })
+    }
+
+    // Matriz de correlación de retornos diarios; se rehace sólo si cambian los datos o el método
+    pub fn correlation_matrix(&self, method: CorrelationMethod) -> Rc<Vec<Vec<f64>>> {
+        self.cache.get_or_replace(IndicatorKey::CorrelationMatrix, self.version(), method, |method| {
+            Rc::new(correlation_matrix(self, *method))
+        })
+    }
+
+    // Correlación y beta móviles del símbolo seleccionado frente al símbolo `benchmark`
+    pub fn rolling_correlation(&self, benchmark: usize, settings: &CorrelationSettings) -> Rc<Vec<(Fecha, f64, f64)>> {
+        let params = (self.selected, benchmark, settings.method, settings.window);
+        self.cache.get_or_replace(IndicatorKey::RollingCorrelation, self.version(), params, |_| {
+            Rc::new(rolling(self.current(), &self.symbols[benchmark], settings))
+        })
+    }
+
+    // Recarga los ficheros que han cambiado en disco y describe lo ocurrido con cada uno
+    pub fn poll_changes(&mut self, config: &Config, adjustments: &[Adjustment]) -> Vec<String> {
+        let mut messages = Vec::new();
+        for symbol in &mut self.symbols {
+            if !symbol.watcher.poll() {
+                continue;
+            }
+            let message = match symbol.reload(config, adjustments) {
+                Ok(Reload::Appended(0)) => format!("{}: última barra actualizada", symbol.name),
+                Ok(Reload::Appended(added)) => format!("{}: {} barra(s) nuevas", symbol.name, added),
+                Ok(Reload::Replaced) => format!("{}: histórico recargado", symbol.name),
+                Err(e) => format!("{}: error al recargar ({})", symbol.name, e),
+            };
+            let issues = symbol.report.issues.len();
+            messages.push(if issues > 0 {
+                format!("{}, {} problema(s) de calidad", message, issues)
+            } else {
+                message
+            });
+        }
+        messages
+    }
+}
+
+// Serie diaria leída de un fichero, con su informe de validación y las notas de los pasos
+// aplicados para mostrarlas al arrancar
+struct Series {
+    report: ValidationReport,
+    daily_bars: Vec<Candle>,
+    notes: Vec<String>,
+}
+
+// Pipeline de carga de un fichero: lectura y preparación de sus filas
+fn read_series(
+    file_path: &str,
+    name: &str,
+    config: &Config,
+    adjustments: &[Adjustment],
+) -> Result<Series, Box<dyn Error>> {
+    let data = load_data(file_path, config.mapping.as_ref())?;
+    if data.is_empty() {
+        return Err(format!("No hay datos disponibles en {}.", file_path).into());
+    }
+    Ok(prepare_series(&data, name, config, adjustments, None))
+}
+
+// Validación, orden cronológico, ajustes y remediación de filas leídas. Si las filas continúan
+// una serie ya cargada (tiempo real), `previous` es su última barra anterior a ellas, para que
+// la remediación rellene el hueco desde ella.
+fn prepare_series(
+    data: &[Data],
+    name: &str,
+    config: &Config,
+    adjustments: &[Adjustment],
+    previous: Option<&Candle>,
+) -> Series {
+    let report = validate(data, &ValidationOptions::default());
+    let mut notes = Vec::new();
+
+    // Velas diarias ordenadas de más antigua a más reciente, sea cual sea el orden del fichero
+    let mut daily_bars: Vec<Candle> = data.iter().map(to_candle).collect();
+    daily_bars.sort_by_key(|c| Fecha::parse(&c.date));
+
+    // Ajustes por splits y dividendos, y después remediación de barras inválidas o faltantes
+    let own: Vec<Adjustment> = adjustments
+        .iter()
+        .filter(|a| a.symbol.as_ref().is_none_or(|s| s == name))
+        .cloned()
+        .collect();
+    if !own.is_empty() {
+        apply_adjustments(&mut daily_bars, &own);
+        notes.push(format!("{} ajuste(s) aplicados", own.len()));
+    }
+    if let Some(method) = config.fill {
+        let bars: Vec<Candle> = previous.into_iter().cloned().chain(daily_bars).collect();
+        let (mut filled, summary) = fill_bars(&bars, method);
+        if let Some(previous) = previous {
+            let start = date(previous);
+            filled.retain(|c| date(c) > start);
+        }
+        notes.push(format!(
+            "remediación ({}): {} barra(s) rellenadas, {} descartadas",
+            method.as_str(),
+            summary.filled,
+            summary.dropped
+        ));
+        daily_bars = filled;
+    }
+
+    Series {
+        report,
+        daily_bars,
+        notes,
+    }
+}
+
+// Con --strict no se aceptan datos con problemas de calidad
+fn check_strict(report: &ValidationReport, file_path: &str, config: &Config) -> Result<(), Box<dyn Error>> {
+    if config.strict && !report.is_clean() {
+        return Err(format!("{} problema(s) de calidad en {} (modo estricto)", report.issues.len(), file_path).into());
+    }
+    Ok(())
+}
+
+// Los directorios se sustituyen por sus ficheros de datos, en orden alfabético
+fn expand_paths(paths: &[String]) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if !Path::new(path).is_dir() {
+            if !Path::new(path).exists() {
+                return Err(format!("El archivo {} no existe.", path).into());
+            }
+            files.push(path.clone());
+            continue;
+        }
+
+        let mut entries: Vec<String> = fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| !is_sidecar(p))
+            .filter(|p| {
+                p.extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| matches!(e.to_lowercase().as_str(), "csv" | "json" | "ndjson" | "jsonl"))
+            })
+            .filter_map(|p| p.to_str().map(str::to_string))
+            .collect();
+        entries.sort();
+        files.extend(entries);
+    }
+    Ok(files)
+}
+
+pub fn to_candle(data: &Data) -> Candle {
+    Candle {
+        date: data.fecha.clone(),
+        open: data.apertura,
+        high: data.maximo,
+        low: data.minimo,
+        close: data.ultimo,
+        volume: data.volumen,
+        gap: false,
+    }
+}
+
+fn date(bar: &Candle) -> Option<Fecha> {
+    Fecha::parse(&bar.date)
+}
+
+fn to_chart_data(bars: &[Candle]) -> Vec<(String, f64)> {
+    bars.iter().map(|c| (c.date.clone(), c.close)).collect()
+}
+
+// Valor que acompaña a cada vista: predicción de precio o indicador técnico
+fn compute_prediction(
+    chart_type: &ChartType,
+    bars: &[Candle],
+    indicators: &IndicatorSet,
+) -> Result<f64, Box<dyn Error>> {
+    let past_days: Vec<f64> = bars.iter().map(|c| c.open).collect();
+    let future_data: Vec<f64> = bars.iter().map(|c| c.close).collect();
+
+    let prediction = match chart_type {
+        ChartType::Candlestick => predict_price(98.0, past_days, future_data, false)?,
+        ChartType::MACD => indicators.macd.value().0,
+        ChartType::SMA => indicators.sma.value(),
+        ChartType::RSI => indicators.rsi.value(),
+        ChartType::BollingerBands => indicators.bollinger.value().1,
+        ChartType::Momentum => indicators.momentum.value(),
+        _ => predict_price(98.0, past_days, future_data, true)?,
+    };
+    Ok(prediction)
+}